@@ -0,0 +1,454 @@
+//! The cat's decision making: which state it is in, where it heads and how far it
+//! goes. Nothing here touches the DOM, `ManzarState` applies the outcomes.
+
+use serde::Deserialize;
+
+use crate::{config::IdleStage, sprites::Point};
+
+// Ticks past the idle timeout before the cat gets tired, and then falls asleep,
+// in the default idle stages
+pub(crate) const TIRED_AFTER: u32 = 20;
+pub(crate) const SLEEP_AFTER: u32 = 41;
+// Ticks before falling asleep the optional yawn starts
+const YAWN_LEAD: u32 = 3;
+
+/// How the cat reacts to the pointer
+#[derive(Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Behavior {
+    /// Run towards the pointer and settle down under it
+    #[default]
+    Chase,
+    /// Run away from the pointer while it is within `flee_range`
+    Flee,
+}
+
+/// What the cat is currently up to, as decided by `render`
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum CatState {
+    Idle,
+    Alert,
+    Moving,
+    Tired,
+    Sleeping,
+    Scratching,
+    Falling,
+    Yawning,
+    Angry,
+    Cowering,
+    Sniffing,
+}
+
+impl CatState {
+    pub(crate) const ALL: [CatState; 11] = [
+        CatState::Idle,
+        CatState::Alert,
+        CatState::Moving,
+        CatState::Tired,
+        CatState::Sleeping,
+        CatState::Scratching,
+        CatState::Falling,
+        CatState::Yawning,
+        CatState::Angry,
+        CatState::Cowering,
+        CatState::Sniffing,
+    ];
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            CatState::Idle => "idle",
+            CatState::Alert => "alert",
+            CatState::Moving => "moving",
+            CatState::Tired => "tired",
+            CatState::Sleeping => "sleeping",
+            CatState::Scratching => "scratching",
+            CatState::Falling => "falling",
+            CatState::Yawning => "yawning",
+            CatState::Angry => "angry",
+            CatState::Cowering => "cowering",
+            CatState::Sniffing => "sniffing",
+        }
+    }
+}
+
+/// Rectangle the cat has to stay in, in viewport coordinates
+#[derive(Clone, Copy)]
+pub(crate) struct Bounds {
+    pub(crate) left: i32,
+    pub(crate) top: i32,
+    pub(crate) right: i32,
+    pub(crate) bottom: i32,
+}
+
+impl Bounds {
+    /// Whether there is no area at all, as with a container that is `display: none`
+    /// or hasn't been laid out yet
+    pub(crate) fn is_empty(&self) -> bool {
+        self.right <= self.left || self.bottom <= self.top
+    }
+
+    /// Gaps between the `size` x `size` sprite around the anchor point and the
+    /// west, north, east and south edges. All of them are 0 for a cat pressed
+    /// into a corner by `clamp`.
+    pub(crate) fn gaps(&self, x: i32, y: i32, anchor: (i32, i32), size: i32) -> [i32; 4] {
+        [
+            x - anchor.0 - self.left,
+            y - anchor.1 - self.top,
+            self.right - (x + size - anchor.0),
+            self.bottom - (y + size - anchor.1),
+        ]
+    }
+
+    /// Pull the cat's anchor point inside the bounds so that the whole
+    /// `size` x `size` sprite around it stays within them
+    pub(crate) fn clamp(&self, x: i32, y: i32, anchor: (i32, i32), size: i32) -> (i32, i32) {
+        // max before min so a container smaller than the cat doesn't panic
+        (
+            x.max(self.left + anchor.0)
+                .min(self.right - (size - anchor.0)),
+            y.max(self.top + anchor.1)
+                .min(self.bottom - (size - anchor.1)),
+        )
+    }
+}
+
+/// Movement keys currently held down, for the `keyboard` control mode
+#[derive(Default)]
+pub(crate) struct HeldKeys(u8);
+
+impl HeldKeys {
+    const UP: u8 = 1;
+    const DOWN: u8 = 1 << 1;
+    const LEFT: u8 = 1 << 2;
+    const RIGHT: u8 = 1 << 3;
+
+    fn bit(key: &str) -> Option<u8> {
+        match key {
+            "ArrowUp" | "w" | "W" => Some(Self::UP),
+            "ArrowDown" | "s" | "S" => Some(Self::DOWN),
+            "ArrowLeft" | "a" | "A" => Some(Self::LEFT),
+            "ArrowRight" | "d" | "D" => Some(Self::RIGHT),
+            _ => None,
+        }
+    }
+
+    /// Whether `key` is one of the keys that move the cat
+    pub(crate) fn moves(key: &str) -> bool {
+        Self::bit(key).is_some()
+    }
+
+    /// Track a key press or release, ignoring keys that don't move the cat
+    pub(crate) fn set(&mut self, key: &str, pressed: bool) {
+        let Some(bit) = Self::bit(key) else {
+            return;
+        };
+        if pressed {
+            self.0 |= bit;
+        } else {
+            self.0 &= !bit;
+        }
+    }
+
+    /// The held direction in the same convention as `ManzarState::heading`
+    pub(crate) fn heading(&self) -> (i8, i8) {
+        let held = |bit: u8| (self.0 & bit != 0) as i8;
+        (
+            held(Self::LEFT) - held(Self::RIGHT),
+            held(Self::UP) - held(Self::DOWN),
+        )
+    }
+}
+
+/// Decide which way the cat faces along one axis, given the normalized movement
+/// component and the currently committed facing (1, 0 or -1). Taking up a facing
+/// requires crossing `threshold + band`, while keeping it only requires staying beyond
+/// `threshold - band`, so jitter around the threshold doesn't flip the sprite.
+pub(crate) fn commit_axis(value: f32, current: i8, threshold: f32, band: f32) -> i8 {
+    let keep = threshold - band;
+    let enter = threshold + band;
+    match current {
+        1 if value > keep => 1,
+        -1 if value < -keep => -1,
+        _ if value > enter => 1,
+        _ if value < -enter => -1,
+        _ => 0,
+    }
+}
+
+/// What an idle cat does next, once it has been idle for longer than the idle timeout
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum IdleAction {
+    // show the idle stage at this index
+    Stage(usize),
+    Scratch,
+    Yawn,
+    // carry on with whatever is showing
+    Continue,
+}
+
+/// Pick the idle action `past_timeout` ticks after the idle timeout. `scratch_due` is
+/// whether this tick is a scratching tick, `yawn` whether yawning is enabled. The
+/// stages are sorted by `after`, which `ManzarConfig::validate` makes sure of.
+pub(crate) fn idle_action(
+    past_timeout: u32,
+    scratch_due: bool,
+    yawn: bool,
+    stages: &[IdleStage],
+) -> IdleAction {
+    let current = stages.iter().rposition(|stage| stage.after <= past_timeout);
+    let asleep_at = stages
+        .iter()
+        .find(|stage| stage.asleep)
+        .map(|stage| stage.after);
+    match current {
+        // a sleeping cat isn't interrupted by scratching
+        Some(index) if stages[index].asleep => IdleAction::Stage(index),
+        _ if scratch_due => IdleAction::Scratch,
+        _ if yawn
+            && asleep_at
+                .is_some_and(|at| (at.saturating_sub(YAWN_LEAD)..at).contains(&past_timeout)) =>
+        {
+            IdleAction::Yawn
+        }
+        Some(index) => IdleAction::Stage(index),
+        None => IdleAction::Continue,
+    }
+}
+
+/// Whether a cat `dist` pixels from its target is done moving. One that is fleeing,
+/// with a `flee_range`, has put enough room between itself and the pointer, and one
+/// that isn't is close enough to count as there, so it doesn't twitch onto the exact pixel.
+pub(crate) fn settled(
+    dist: f32,
+    speed: f32,
+    arrive_threshold: f32,
+    flee_range: Option<f32>,
+) -> bool {
+    match flee_range {
+        Some(range) => dist >= range,
+        None => dist < speed || dist < arrive_threshold,
+    }
+}
+
+/// Where a cat at `position` ends up after a tick of heading along `direction`, the
+/// unit vector `direction` returns. The step is `speed` pixels long whichever way the
+/// cat goes, unless `smoothing` has it cover that share of what is left to `target`.
+pub(crate) fn step(
+    position: (f32, f32),
+    target: &Point,
+    direction: (f32, f32),
+    speed: f32,
+    smoothing: f32,
+) -> (f32, f32) {
+    if smoothing > 0.0 {
+        // easing in on the target
+        return (
+            position.0 + (target.0 as f32 - position.0) * smoothing,
+            position.1 + (target.1 as f32 - position.1) * smoothing,
+        );
+    }
+    (
+        position.0 - direction.0 * speed,
+        position.1 - direction.1 * speed,
+    )
+}
+
+/// Distance from the cat to its target
+pub(crate) fn distance(cat: &Point, target: &Point) -> f32 {
+    // squared in i64 so large coordinates can't overflow
+    let (dx, dy) = offset(cat, target);
+    ((dx.pow(2) + dy.pow(2)) as f64).sqrt() as f32
+}
+
+/// Unit vector pointing from the target back to the cat, so positive components
+/// mean the cat has to head west/north like `heading`. None when they coincide.
+pub(crate) fn direction(cat: &Point, target: &Point) -> Option<(f32, f32)> {
+    let (dx, dy) = offset(cat, target);
+    unit(dx as f32, dy as f32)
+}
+
+/// `(x, y)` scaled to a length of 1, so a step of `speed` along it covers the same
+/// distance on a diagonal as along an axis. None for (0, 0).
+pub(crate) fn unit(x: f32, y: f32) -> Option<(f32, f32)> {
+    let len = x.hypot(y);
+    // dividing by a zero length would write NaN positions to the style
+    if len == 0.0 {
+        return None;
+    }
+    Some((x / len, y / len))
+}
+
+fn offset(cat: &Point, target: &Point) -> (i64, i64) {
+    (
+        cat.0 as i64 - target.0 as i64,
+        cat.1 as i64 - target.1 as i64,
+    )
+}
+
+/// Compass name of a committed heading, e.g. `"NW"`. Empty if the heading is (0, 0).
+pub(crate) fn compass(heading: (i8, i8)) -> &'static str {
+    match heading {
+        (0, 1) => "N",
+        (-1, 1) => "NE",
+        (-1, 0) => "E",
+        (-1, -1) => "SE",
+        (0, -1) => "S",
+        (1, -1) => "SW",
+        (1, 0) => "W",
+        (1, 1) => "NW",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ManzarConfig;
+
+    const BOUNDS: Bounds = Bounds {
+        left: 0,
+        top: 0,
+        right: 200,
+        bottom: 100,
+    };
+
+    #[test]
+    fn steps_are_speed_long_in_every_direction() {
+        let target = Point(0, 0);
+        for cat in [Point(100, 0), Point(0, -100), Point(70, 70), Point(-30, 40)] {
+            let direction = direction(&cat, &target).unwrap();
+            let position = (cat.0 as f32, cat.1 as f32);
+            let (x, y) = step(position, &target, direction, 10.0, 0.0);
+            let travelled = (x - position.0).hypot(y - position.1);
+            assert!((travelled - 10.0).abs() < 1e-4, "{}", travelled);
+            // and towards the target, not away from it
+            assert!(x.hypot(y) < position.0.hypot(position.1));
+        }
+    }
+
+    #[test]
+    fn smoothing_covers_a_share_of_the_gap() {
+        let (x, y) = step((100.0, 50.0), &Point(0, 0), (1.0, 0.0), 10.0, 0.25);
+        assert_eq!((x, y), (75.0, 37.5));
+    }
+
+    #[test]
+    fn settles_within_a_step_or_the_arrive_threshold() {
+        assert!(settled(4.0, 5.0, 0.0, None));
+        assert!(!settled(5.0, 5.0, 0.0, None));
+        assert!(settled(5.0, 5.0, 6.0, None));
+        // nothing counts as close enough without a speed or threshold
+        assert!(!settled(0.5, 0.0, 0.0, None));
+    }
+
+    #[test]
+    fn a_fleeing_cat_settles_out_of_range() {
+        assert!(!settled(199.0, 5.0, 0.0, Some(200.0)));
+        assert!(settled(200.0, 5.0, 0.0, Some(200.0)));
+        // being right under the pointer is the opposite of settled
+        assert!(!settled(0.0, 5.0, 10.0, Some(200.0)));
+    }
+
+    #[test]
+    fn direction_points_from_the_target_back_to_the_cat() {
+        assert_eq!(direction(&Point(10, 0), &Point(0, 0)), Some((1.0, 0.0)));
+        assert_eq!(direction(&Point(0, 0), &Point(0, 10)), Some((0.0, -1.0)));
+        assert_eq!(direction(&Point(3, 3), &Point(3, 3)), None);
+        assert_eq!(unit(0.0, 0.0), None);
+    }
+
+    #[test]
+    fn facing_needs_the_threshold_crossed_and_sticks_within_the_band() {
+        assert_eq!(commit_axis(0.55, 0, 0.5, 0.1), 0);
+        assert_eq!(commit_axis(0.65, 0, 0.5, 0.1), 1);
+        assert_eq!(commit_axis(0.45, 1, 0.5, 0.1), 1);
+        assert_eq!(commit_axis(0.35, 1, 0.5, 0.1), 0);
+        assert_eq!(commit_axis(-0.65, 1, 0.5, 0.1), -1);
+    }
+
+    #[test]
+    fn facing_maps_to_compass_names() {
+        assert_eq!(compass((1, 1)), "NW");
+        assert_eq!(compass((-1, 0)), "E");
+        assert_eq!(compass((0, -1)), "S");
+        assert_eq!(compass((0, 0)), "");
+    }
+
+    #[test]
+    fn idle_goes_through_the_stages_in_order() {
+        let stages = ManzarConfig::default().idle_stages;
+        assert!(idle_action(0, false, false, &stages) == IdleAction::Continue);
+        assert!(idle_action(TIRED_AFTER, false, false, &stages) == IdleAction::Stage(0));
+        assert!(idle_action(SLEEP_AFTER - 1, false, false, &stages) == IdleAction::Stage(0));
+        assert!(idle_action(SLEEP_AFTER, false, false, &stages) == IdleAction::Stage(1));
+        assert!(idle_action(u32::MAX, false, false, &stages) == IdleAction::Stage(1));
+    }
+
+    #[test]
+    fn scratching_and_yawning_come_before_sleep_but_dont_interrupt_it() {
+        let stages = ManzarConfig::default().idle_stages;
+        assert!(idle_action(TIRED_AFTER, true, false, &stages) == IdleAction::Scratch);
+        assert!(idle_action(SLEEP_AFTER, true, false, &stages) == IdleAction::Stage(1));
+        assert!(idle_action(SLEEP_AFTER - 1, false, true, &stages) == IdleAction::Yawn);
+        assert!(
+            idle_action(SLEEP_AFTER - YAWN_LEAD - 1, false, true, &stages) == IdleAction::Stage(0)
+        );
+        assert!(idle_action(SLEEP_AFTER, false, true, &stages) == IdleAction::Stage(1));
+    }
+
+    #[test]
+    fn clamping_keeps_the_whole_sprite_inside() {
+        let anchor = (16, 16);
+        assert_eq!(BOUNDS.clamp(-50, 50, anchor, 32), (16, 50));
+        assert_eq!(BOUNDS.clamp(500, 500, anchor, 32), (184, 84));
+        assert_eq!(BOUNDS.clamp(100, 50, anchor, 32), (100, 50));
+        // measured from the anchor, which needn't be the center
+        assert_eq!(BOUNDS.clamp(0, 0, (0, 32), 32), (0, 32));
+    }
+
+    #[test]
+    fn clamping_into_bounds_smaller_than_the_cat_doesnt_panic() {
+        let tiny = Bounds {
+            left: 0,
+            top: 0,
+            right: 10,
+            bottom: 10,
+        };
+        let (x, y) = tiny.clamp(100, 100, (16, 16), 32);
+        assert_eq!((x, y), (-6, -6));
+        assert!(Bounds { right: 0, ..tiny }.is_empty());
+    }
+
+    #[test]
+    fn a_clamped_cat_has_no_gap_to_the_walls_it_was_pushed_against() {
+        let (x, y) = BOUNDS.clamp(500, -50, (16, 16), 32);
+        assert_eq!(BOUNDS.gaps(x, y, (16, 16), 32), [168, 0, 0, 68]);
+    }
+
+    #[test]
+    fn held_keys_combine_into_a_heading() {
+        let mut keys = HeldKeys::default();
+        keys.set("ArrowUp", true);
+        keys.set("a", true);
+        keys.set("Enter", true);
+        assert_eq!(keys.heading(), (1, 1));
+        keys.set("ArrowUp", false);
+        assert_eq!(keys.heading(), (1, 0));
+        assert!(HeldKeys::moves("D") && !HeldKeys::moves("Enter"));
+    }
+
+    #[test]
+    fn far_apart_points_dont_overflow() {
+        let half = i32::MAX / 2;
+        let far = Point(half, half);
+        let expected = (half as f64 * std::f64::consts::SQRT_2) as f32;
+        let dist = distance(&Point(0, 0), &far);
+        assert!((dist - expected).abs() / expected < 1e-6, "{}", dist);
+        // as far as the two ends of the range get, too
+        assert!(distance(&Point(-half, -half), &far) > dist);
+
+        let (dx, dy) = direction(&Point(0, 0), &far).unwrap();
+        assert!((dx.hypot(dy) - 1.0).abs() < 1e-6);
+        assert!(dx < 0.0 && dy < 0.0);
+    }
+}
@@ -0,0 +1,103 @@
+use std::rc::Rc;
+
+use crate::{
+    clock::{Clock, WebClock},
+    config::{ManzarConfig, Personality},
+    error::ManzarError,
+    sprites::ManzarSprites,
+    start_manzar, Manzar,
+};
+
+/// Chainable Rust-side alternative to passing a config object to `start`.
+///
+/// ```ignore
+/// let cat = ManzarBuilder::default()
+///     .sheet_url("./kitty.gif")
+///     .speed(12)
+///     .build()?;
+/// ```
+#[derive(Default)]
+pub struct ManzarBuilder {
+    config: ManzarConfig,
+    clock: Option<Rc<dyn Clock>>,
+    sprites: Option<ManzarSprites>,
+}
+
+impl ManzarBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from an existing config, e.g. one deserialized from JS
+    pub fn from_config(config: ManzarConfig) -> Self {
+        Self {
+            config,
+            clock: None,
+            sprites: None,
+        }
+    }
+
+    /// Take on the options of `personality`. Options set later in the chain override
+    /// its values, options set earlier are replaced.
+    pub fn personality(mut self, personality: Personality) -> Self {
+        self.config.personality = Some(personality);
+        personality.apply(&mut self.config, |_| false);
+        self
+    }
+
+    pub fn speed(mut self, speed: i32) -> Self {
+        self.config.speed = speed;
+        self
+    }
+
+    pub fn sheet_url(mut self, url: impl Into<String>) -> Self {
+        self.config.sheet_url = url.into();
+        self
+    }
+
+    pub fn tile_size(mut self, size: i32) -> Self {
+        self.config.tile_size = size;
+        self
+    }
+
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.config.scale = scale;
+        self
+    }
+
+    pub fn idle_timeout(mut self, ticks: u32) -> Self {
+        self.config.idle_timeout = ticks;
+        self
+    }
+
+    pub fn start_position(mut self, x: i32, y: i32) -> Self {
+        self.config.start_position = (x, y);
+        self
+    }
+
+    /// Only advance the cat when `Manzar::step` is called, instead of on a timer
+    pub fn manual_step(mut self, manual: bool) -> Self {
+        self.config.manual_step = manual;
+        self
+    }
+
+    /// Read the time from `clock` instead of `performance.now()`
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Rc::new(clock));
+        self
+    }
+
+    /// Draw the cat with `sprites` instead of the `kitty.gif` layout. Their animation
+    /// speeds are kept, so `animation_speeds` doesn't apply.
+    pub fn sprites(mut self, sprites: ManzarSprites) -> Self {
+        self.sprites = Some(sprites);
+        self
+    }
+
+    /// Spawn the cat on the current page and return a handle to it. Unlike `start`,
+    /// this doesn't wait for the body, and fails with `NoBody` if there isn't one yet.
+    pub fn build(self) -> Result<Manzar, ManzarError> {
+        let clock = self.clock.unwrap_or_else(|| Rc::new(WebClock));
+        start_manzar(self.config, clock, self.sprites)
+    }
+}
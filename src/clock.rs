@@ -0,0 +1,46 @@
+//! Where the cat gets the time from. Everything time-based reads it through a
+//! `Clock`, so embedders (and tests) can swap in a `ManualClock` and drive time
+//! by hand.
+
+use std::{cell::Cell, rc::Rc};
+
+pub trait Clock {
+    /// Milliseconds since some fixed point, never going backwards
+    fn now(&self) -> f64;
+}
+
+/// The page's `performance.now()`, the same timeline as event timestamps
+#[derive(Clone, Copy, Default)]
+pub struct WebClock;
+
+impl Clock for WebClock {
+    fn now(&self) -> f64 {
+        web_sys::window()
+            .and_then(|window| window.performance())
+            .map_or_else(js_sys::Date::now, |performance| performance.now())
+    }
+}
+
+/// A clock that only moves when told to. Clones share the same time.
+#[derive(Clone, Default)]
+pub struct ManualClock(Rc<Cell<f64>>);
+
+impl ManualClock {
+    pub fn new(start: f64) -> Self {
+        Self(Rc::new(Cell::new(start)))
+    }
+
+    pub fn advance(&self, ms: f64) {
+        self.0.set(self.0.get() + ms);
+    }
+
+    pub fn set(&self, ms: f64) {
+        self.0.set(ms);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> f64 {
+        self.0.get()
+    }
+}
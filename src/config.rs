@@ -0,0 +1,771 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::{
+    behavior::{Behavior, CatState, SLEEP_AFTER, TIRED_AFTER},
+    error::ManzarError,
+    render::RendererKind,
+};
+
+/// Options accepted by `start`, read from a plain JS object.
+/// Every field is optional and falls back to its default.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct ManzarConfig {
+    /// Preset temperament filling in the options it covers that aren't given
+    /// explicitly. Applied by `start` and `ManzarBuilder::personality`, in Rust
+    /// `Personality::config` is the way to start from one.
+    pub personality: Option<Personality>,
+    /// Pixels the cat moves per tick
+    pub speed: i32,
+    /// Time between two ticks of the render loop, in ms. Speeds, durations and
+    /// animation speeds are all counted in these ticks.
+    pub tick_interval: u32,
+    /// Don't run the internal render loop. The cat only advances when `Manzar::step`
+    /// is called, e.g. from the page's own animation loop or a test.
+    pub manual_step: bool,
+    /// Tick only every this many ms while the cat is asleep, to save battery.
+    /// The normal rate comes back as soon as the cat wakes up, whatever woke it.
+    pub sleep_tick_interval: Option<u32>,
+    /// Apply `set_speed` right away instead of easing into the new speed
+    pub instant_speed: bool,
+    /// Instead of moving `speed` pixels, cover this share (0 to 1) of the remaining
+    /// distance every tick. 0 keeps the constant speed.
+    pub smoothing: f32,
+    /// Once closer to its target than this many pixels, the cat counts as arrived and
+    /// stops instead of taking a last tiny step. 0 leaves it to the `speed` check.
+    pub arrive_threshold: f32,
+    /// URL of the sprite sheet
+    pub sheet_url: String,
+    /// Width and height of a single frame on the sheet, in pixels
+    pub tile_size: i32,
+    /// Separate sheets for some states, keyed by state name as in the statechange
+    /// event. Those states' frames are then offsets on their own sheet.
+    pub state_sheets: HashMap<String, String>,
+    /// Columns and rows of frames on the sheet. When given, `tile_size` is worked out
+    /// from the sheet's dimensions once it has loaded.
+    pub sheet_grid: Option<(u32, u32)>,
+    /// Where on the sheet each state's frames are. Defaults to the `kitty.gif` layout.
+    pub sheet_layout: SheetLayout,
+    /// How much bigger (or smaller) than on the sheet the cat is drawn
+    pub scale: f32,
+    /// How the cat's sub-pixel position is put on whole pixels of the page
+    pub position_rounding: PositionRounding,
+    /// Targets farther away than this many pixels aren't chased, the cat stays
+    /// where it is until they come back within range
+    pub max_chase_distance: Option<f32>,
+    /// `"chase"` runs after the pointer, `"flee"` runs away from it
+    pub behavior: Behavior,
+    /// In flee mode, the cat only runs from a pointer closer than this many pixels
+    pub flee_range: f32,
+    /// Ticks the cat stays idle before it starts getting sleepy
+    pub idle_timeout: u32,
+    /// What the cat goes through while it stays idle past `idle_timeout`, in order.
+    /// Defaults to getting tired and then falling asleep.
+    pub idle_stages: Vec<IdleStage>,
+    /// Where the cat goes to sleep once it has been idle long enough, instead
+    /// of sleeping wherever it stopped
+    pub home: Option<Home>,
+    /// Ticks the cat looks alert before it starts chasing a new target. 0 skips the alert.
+    pub alert_frames: u32,
+    /// Set to false to have the cat set off right away, without ever looking alert first
+    pub alert_enabled: bool,
+    /// Pointer movement of more than this many pixels restarts the idle timeout
+    pub activity_deadzone: i32,
+    /// Point within a frame, in pixels from its top left corner, that is placed on the
+    /// cat's position (and so on the cursor). Defaults to the center of the tile.
+    pub anchor_x: Option<i32>,
+    pub anchor_y: Option<i32>,
+    /// Where the cat (and the initial target) is placed on spawn
+    pub start_position: (i32, i32),
+    /// While idle, the cat tries scratching on every tick that is a multiple of this.
+    /// Must be larger than `idle_timeout`, otherwise scratching interrupts the idle cycle.
+    pub scratch_frequency: u32,
+    /// Ticks a scratch lasts. Has to be a whole number of cycles of every scratch
+    /// animation, so none is cut off halfway. Defaults to 20.
+    pub scratch_duration: Option<u32>,
+    pub scratch_frames: ScratchFrames,
+    /// Ticks after a scratch before the cat can scratch again
+    pub scratch_cooldown: u32,
+    /// Scratching closer than this many pixels to a wall of the bounds scratches on
+    /// the wall instead of in the open
+    pub scratch_margin: u32,
+    /// Set to false to skip scratching and go straight from idle to tired and asleep
+    pub scratch_enabled: bool,
+    /// Reuse the east facing frames for west facing movement by mirroring them,
+    /// for sheets that only draw one horizontal facing. The resting states are then
+    /// drawn facing west, and mirrored when the cat last walked east.
+    pub mirror: bool,
+    /// How far the normalized movement has to lean along an axis before the cat
+    /// faces that way, e.g. 0.5 turns north once more than half the motion is northward
+    pub direction_threshold: f32,
+    /// Half-width of the band around `direction_threshold` in which the cat keeps
+    /// its current facing, so cursor jitter doesn't make the sprite flicker
+    pub direction_hysteresis: f32,
+    /// Steer the cat with the arrow keys or WASD instead of having it chase the cursor
+    pub keyboard: bool,
+    /// Stay put and only turn to look at the cursor while it moves
+    pub face_only: bool,
+    /// Let the cat be picked up with the pointer and thrown across the page
+    pub draggable: bool,
+    /// Fraction of a thrown cat's velocity kept on each tick, between 0 and 1
+    pub throw_friction: f32,
+    /// Make the cat fall to the bottom of the viewport whenever it isn't chasing
+    pub gravity: bool,
+    /// In gravity mode, the cat only leaps for targets closer than this many pixels
+    pub leap_range: f32,
+    /// CSS selector of an element whose rectangle the cat can't leave,
+    /// instead of roaming the whole page
+    pub confine_to: Option<String>,
+    /// `"dom"` moves a div per cat, `"canvas"` draws every cat onto one shared canvas
+    /// and `"batch"` redraws them all together. Only the dom backend supports
+    /// `draggable`, `trail` and `angry`, as the others draw where clicks can't reach.
+    pub renderer: RendererKind,
+    /// Remember the cat's position in `localStorage` and put it back there on the next load
+    pub persist: bool,
+    /// `localStorage` key the position is kept under, change it to keep several cats apart
+    pub storage_key: String,
+    /// e.g. `{ sleeping: 10 }` for slower breathing while asleep
+    pub animation_speeds: AnimationSpeeds,
+    /// Keep the cat off the page until `reveal` is called, e.g. to not distract on load
+    pub start_hidden: bool,
+    /// With `start_hidden`, also reveal the cat the first time the pointer moves
+    pub reveal_on_move: bool,
+    /// Play the `poof_in` sprite when the cat appears and `poof_out` when it is
+    /// destroyed. kitty.gif has no poof frames, so this needs a sheet that does.
+    pub poof: bool,
+    /// Take a short walk around the start position after spawning, before following
+    /// the pointer. Moving the pointer cuts the walk short.
+    pub intro: bool,
+    /// Glance one way and then the other shortly after settling down
+    pub look_around: bool,
+    /// Walk over to sniff at wherever the page is clicked, and then go on to the pointer
+    pub curious: bool,
+    /// With `curious`, ticks the cat sniffs at the clicked spot for
+    pub sniff_ticks: u32,
+    /// Play a one-shot yawn right before the cat falls asleep
+    pub yawn: bool,
+    /// Chance per tick that an idle cat plays a short fidget, from 0 (never) to 1
+    pub fidget_chance: f32,
+    /// The fidgets picked from, each with a weight for how often it plays. Without
+    /// any, the cat always plays the `fidget` sprite.
+    pub fidgets: Vec<Fidget>,
+    /// Seed for the random behaviors, so they play out the same way every time.
+    /// Picked at random when left out.
+    pub seed: Option<u64>,
+    /// Fade the cat in when it spawns and out when it is hidden, in ms.
+    /// 0 shows and hides it instantly. Only supported by the dom renderer.
+    pub fade_duration: u32,
+    /// Get angry when the cat itself is clicked too often. Needs the dom renderer.
+    pub angry: bool,
+    /// Clicks within `angry_window` that make the cat angry
+    pub angry_clicks: u32,
+    /// Time span the clicks are counted over, in ms
+    pub angry_window: f64,
+    /// Ticks the cat stays angry before going back to normal
+    pub angry_cooldown: u32,
+    /// Hold still while the browser's context menu is open, until the next click or key press
+    pub freeze_on_context_menu: bool,
+    /// Leave fading copies of the cat behind it while it moves.
+    /// Off by default since every copy is an extra DOM node.
+    pub trail: bool,
+    /// Number of trail copies. They are created once and then reused, so this is
+    /// also how many extra DOM nodes the trail adds to the page.
+    pub trail_length: usize,
+    /// Prepended to the names of dispatched DOM events, e.g. `manzar:statechange`
+    pub event_prefix: String,
+    /// Id of the cat's element. Further cats with the same prefix get `-2`, `-3`, ... appended.
+    /// Event names don't change with it, the events carry the id in `detail.id` instead.
+    pub id_prefix: String,
+    /// Overlay the cat's live state, distance, frame and position, see also
+    /// `Manzar::set_debug`. Only available when built with the `debug` feature.
+    #[cfg(feature = "debug")]
+    pub debug: bool,
+}
+
+/// A named temperament, setting the options behind how eager, sleepy or timid the cat is
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Personality {
+    /// Slow, and quick to doze off
+    Lazy,
+    /// Fast, fidgety and hard to tire out
+    Hyper,
+    /// Keeps its distance from the pointer
+    Shy,
+}
+
+impl Personality {
+    /// The default config with this personality's options filled in
+    pub fn config(self) -> ManzarConfig {
+        let mut config = ManzarConfig {
+            personality: Some(self),
+            ..ManzarConfig::default()
+        };
+        self.apply(&mut config, |_| false);
+        config
+    }
+
+    /// Fill in this personality's options, leaving out the ones `is_set` says were
+    /// given explicitly, by their name in the JS config
+    pub(crate) fn apply(self, config: &mut ManzarConfig, is_set: impl Fn(&str) -> bool) {
+        let unset = |option: &str| !is_set(option);
+        match self {
+            Personality::Lazy => {
+                if unset("speed") {
+                    config.speed = 6;
+                }
+                if unset("alert_frames") {
+                    config.alert_frames = 10;
+                }
+                if unset("idle_timeout") {
+                    config.idle_timeout = 20;
+                }
+                if unset("idle_stages") {
+                    config.idle_stages = vec![
+                        IdleStage::new(8, "tired", false),
+                        IdleStage::new(20, "sleeping", true),
+                    ];
+                }
+                if unset("yawn") {
+                    config.yawn = true;
+                }
+            }
+            Personality::Hyper => {
+                if unset("speed") {
+                    config.speed = 18;
+                }
+                if unset("alert_frames") {
+                    config.alert_frames = 1;
+                }
+                if unset("idle_timeout") {
+                    config.idle_timeout = 150;
+                }
+                // has to stay above the idle timeout
+                if unset("scratch_frequency") {
+                    config.scratch_frequency = 211;
+                }
+                if unset("fidget_chance") {
+                    config.fidget_chance = 0.05;
+                }
+            }
+            Personality::Shy => {
+                if unset("behavior") {
+                    config.behavior = Behavior::Flee;
+                }
+                if unset("flee_range") {
+                    config.flee_range = 250.0;
+                }
+                if unset("speed") {
+                    config.speed = 14;
+                }
+                if unset("alert_frames") {
+                    config.alert_frames = 0;
+                }
+            }
+        }
+    }
+}
+
+/// Animation speeds of the resting states, in percent of one frame per tick.
+/// Only matters for states whose sprite is animated.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct AnimationSpeeds {
+    pub idle: u32,
+    pub alert: u32,
+    pub tired: u32,
+    pub sleeping: u32,
+}
+
+impl Default for AnimationSpeeds {
+    fn default() -> Self {
+        Self {
+            idle: 100,
+            alert: 100,
+            tired: 100,
+            sleeping: 25,
+        }
+    }
+}
+
+/// How the frames are arranged on the sheet: the name of a bundled layout like
+/// `"kitty"`, or `[x, y]` tile offsets by sprite name, e.g. `{ sleeping: [[-2, 0], [-2, -1]] }`.
+/// Sprites left out keep their `kitty.gif` frames.
+#[derive(Clone, Deserialize)]
+#[serde(untagged)]
+pub enum SheetLayout {
+    Named(NamedLayout),
+    Frames(HashMap<String, Vec<(i32, i32)>>),
+}
+
+impl Default for SheetLayout {
+    fn default() -> Self {
+        SheetLayout::Named(NamedLayout::Kitty)
+    }
+}
+
+/// The layouts that ship with the crate
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NamedLayout {
+    /// The 8x4 `kitty.gif` sheet from oneko
+    Kitty,
+}
+
+/// `"round"` or `"floor"` the position to whole pixels, or also snap the cat's
+/// top left corner to multiples of some pixels with e.g. `{ grid: 4 }`
+#[derive(Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PositionRounding {
+    #[default]
+    Round,
+    Floor,
+    Grid(u32),
+}
+
+impl PositionRounding {
+    /// The whole pixel a sub-pixel coordinate ends up on
+    pub(crate) fn pixel(self, value: f32) -> i32 {
+        match self {
+            PositionRounding::Floor => value.floor() as i32,
+            _ => value.round() as i32,
+        }
+    }
+
+    /// Where the corner of the sprite is drawn for a corner at `pixel`
+    pub(crate) fn snap(self, pixel: i32) -> i32 {
+        match self {
+            PositionRounding::Grid(size) if size > 1 => {
+                let size = size as f32;
+                ((pixel as f32 / size).round() * size) as i32
+            }
+            _ => pixel,
+        }
+    }
+}
+
+/// A point on the page as `[x, y]`, or one of the corners of the cat's bounds
+/// as e.g. `"bottom_right"`
+#[derive(Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Home {
+    Point(i32, i32),
+    Corner(Corner),
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// What a follower does differently from its leader, see `spawn_following`, e.g.
+/// `{ speed: 4, lag: 60, sheet_url: "./kitten.gif" }`. Anything left out is taken
+/// over from the leader.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct FollowerOptions {
+    /// Pixels per tick, instead of the leader's `speed`
+    pub speed: Option<i32>,
+    /// How far its spot may get from a resting follower before it sets off after it,
+    /// in pixels. 0 follows every step of the leader.
+    pub lag: f32,
+    /// Sprite sheet laid out like the leader's, used instead of its sheet and `state_sheets`
+    pub sheet_url: Option<String>,
+}
+
+impl FollowerOptions {
+    /// Turn a copy of the leader's config into the follower's
+    pub(crate) fn apply(&self, config: &mut ManzarConfig) -> Result<(), ManzarError> {
+        if !self.lag.is_finite() || self.lag < 0.0 {
+            return Err(ManzarError::InvalidConfig(String::from(
+                "lag must be a finite number of at least 0",
+            )));
+        }
+        // the leader already owns the keys and the stored position
+        config.keyboard = false;
+        config.persist = false;
+        config.intro = false;
+        if let Some(speed) = self.speed {
+            config.speed = speed;
+        }
+        if let Some(sheet_url) = &self.sheet_url {
+            config.sheet_url = sheet_url.clone();
+            config.state_sheets.clear();
+        }
+        Ok(())
+    }
+}
+
+/// A short animation an idle cat plays now and then, e.g.
+/// `{ sprite: "blink", frames: [[-3, -3], [0, 0]], weight: 5 }`
+#[derive(Clone, Deserialize)]
+pub struct Fidget {
+    /// Name of one of the state sprites, or of a new sprite made from `frames`
+    pub sprite: String,
+    /// `[x, y]` tile offsets played through once, a tick each
+    #[serde(default)]
+    pub frames: Option<Vec<(i32, i32)>>,
+    /// How often this fidget plays compared to the others. Defaults to 1.
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+/// One step of the idle cat's wind-down, e.g. `{ after: 20, sprite: "tired" }`
+#[derive(Clone, Deserialize)]
+pub struct IdleStage {
+    /// Ticks past `idle_timeout` the stage starts at
+    pub after: u32,
+    /// Name of one of the state sprites, as in `set_state_sprite`, or of a new
+    /// sprite made from `frames`
+    pub sprite: String,
+    /// `[x, y]` tile offsets looped through while the stage lasts, replacing the
+    /// frames of `sprite`
+    #[serde(default)]
+    pub frames: Option<Vec<(i32, i32)>>,
+    /// The cat counts as asleep from here on. Scratching doesn't wake it and the
+    /// yawn plays right before the first such stage.
+    #[serde(default)]
+    pub asleep: bool,
+}
+
+impl IdleStage {
+    fn new(after: u32, sprite: &str, asleep: bool) -> Self {
+        Self {
+            after,
+            sprite: String::from(sprite),
+            frames: None,
+            asleep,
+        }
+    }
+}
+
+/// The error for a speed below 0, which would have the cat run from its target
+pub(crate) fn negative_speed(speed: i32) -> ManzarError {
+    ManzarError::InvalidConfig(format!("speed must be 0 or more, got {}", speed))
+}
+
+/// Replacement frames for the scratching animations, as `[x, y]` tile offsets.
+/// `cat` is scratching itself away from walls, the rest are per wall.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ScratchFrames {
+    pub cat: Option<Vec<(i32, i32)>>,
+    pub n: Option<Vec<(i32, i32)>>,
+    pub e: Option<Vec<(i32, i32)>>,
+    pub s: Option<Vec<(i32, i32)>>,
+    pub w: Option<Vec<(i32, i32)>>,
+}
+
+impl Default for ManzarConfig {
+    fn default() -> Self {
+        Self {
+            personality: None,
+            speed: 10,
+            tick_interval: 100,
+            manual_step: false,
+            sleep_tick_interval: None,
+            instant_speed: false,
+            smoothing: 0.0,
+            arrive_threshold: 0.0,
+            sheet_url: String::from("./kitty.gif"),
+            tile_size: 32,
+            position_rounding: PositionRounding::Round,
+            state_sheets: HashMap::new(),
+            sheet_grid: None,
+            sheet_layout: SheetLayout::default(),
+            scale: 1.0,
+            max_chase_distance: None,
+            behavior: Behavior::Chase,
+            flee_range: 200.0,
+            idle_timeout: 50,
+            idle_stages: vec![
+                IdleStage::new(TIRED_AFTER, "tired", false),
+                IdleStage::new(SLEEP_AFTER, "sleeping", true),
+            ],
+            home: None,
+            alert_frames: 5,
+            alert_enabled: true,
+            activity_deadzone: 3,
+            anchor_x: None,
+            anchor_y: None,
+            start_position: (32, 32),
+            scratch_frequency: 101,
+            scratch_duration: None,
+            scratch_frames: ScratchFrames::default(),
+            scratch_cooldown: 0,
+            scratch_margin: 10,
+            scratch_enabled: true,
+            mirror: false,
+            direction_threshold: 0.5,
+            direction_hysteresis: 0.1,
+            keyboard: false,
+            face_only: false,
+            draggable: false,
+            throw_friction: 0.85,
+            gravity: false,
+            leap_range: 200.0,
+            confine_to: None,
+            renderer: RendererKind::Dom,
+            persist: false,
+            storage_key: String::from("manzar"),
+            animation_speeds: AnimationSpeeds::default(),
+            start_hidden: false,
+            reveal_on_move: false,
+            poof: false,
+            intro: false,
+            look_around: false,
+            curious: false,
+            sniff_ticks: 10,
+            yawn: false,
+            fidget_chance: 0.0,
+            fidgets: Vec::new(),
+            seed: None,
+            fade_duration: 0,
+            angry: false,
+            angry_clicks: 5,
+            angry_window: 1500.0,
+            angry_cooldown: 30,
+            freeze_on_context_menu: true,
+            trail: false,
+            trail_length: 5,
+            event_prefix: String::from("manzar"),
+            id_prefix: String::from("Manzar"),
+            #[cfg(feature = "debug")]
+            debug: false,
+        }
+    }
+}
+
+impl ManzarConfig {
+    /// Width and height of the cat on the page, in pixels
+    pub fn size(&self) -> i32 {
+        (self.tile_size as f32 * self.scale).round() as i32
+    }
+
+    /// The anchor offset with the tile-centered defaults filled in, scaled
+    /// to pixels on the page
+    pub fn anchor(&self) -> (i32, i32) {
+        let center = self.tile_size / 2;
+        let scale = |offset: i32| (offset as f32 * self.scale).round() as i32;
+        (
+            scale(self.anchor_x.unwrap_or(center)),
+            scale(self.anchor_y.unwrap_or(center)),
+        )
+    }
+
+    /// Ticks the cat looks alert before chasing, 0 with the alert turned off
+    pub(crate) fn alert_ticks(&self) -> u32 {
+        if self.alert_enabled {
+            self.alert_frames
+        } else {
+            0
+        }
+    }
+
+    /// Reject combinations of options the cat can't behave sensibly with
+    pub fn validate(&self) -> Result<(), ManzarError> {
+        if self.speed < 0 {
+            return Err(negative_speed(self.speed));
+        }
+        if self.scratch_enabled && self.scratch_frequency <= self.idle_timeout {
+            return Err(ManzarError::InvalidConfig(format!(
+                "scratch_frequency ({}) must be greater than idle_timeout ({})",
+                self.scratch_frequency, self.idle_timeout
+            )));
+        }
+        if self.renderer != RendererKind::Dom && (self.draggable || self.trail || self.angry) {
+            return Err(ManzarError::InvalidConfig(String::from(
+                "draggable, trail and angry need the dom renderer",
+            )));
+        }
+
+        if let Some(name) = self
+            .state_sheets
+            .keys()
+            .find(|name| !CatState::ALL.iter().any(|state| state.name() == *name))
+        {
+            return Err(ManzarError::InvalidConfig(format!(
+                "state_sheets has a sheet for unknown state {:?}",
+                name
+            )));
+        }
+
+        if let Some(pair) = self
+            .idle_stages
+            .windows(2)
+            .find(|pair| pair[0].after >= pair[1].after)
+        {
+            return Err(ManzarError::InvalidConfig(format!(
+                "idle_stages must start at strictly increasing ticks, but {:?} at {} is followed by {:?} at {}",
+                pair[0].sprite, pair[0].after, pair[1].sprite, pair[1].after
+            )));
+        }
+
+        if let Some((0, _) | (_, 0)) = self.sheet_grid {
+            return Err(ManzarError::InvalidConfig(String::from(
+                "sheet_grid needs at least one column and row",
+            )));
+        }
+
+        if !(self.scale.is_finite() && self.size() > 0) {
+            return Err(ManzarError::InvalidConfig(format!(
+                "scale must leave the cat at least a pixel wide, got {}",
+                self.scale
+            )));
+        }
+
+        if self.position_rounding == PositionRounding::Grid(0) {
+            return Err(ManzarError::InvalidConfig(String::from(
+                "position_rounding needs a grid of at least 1 pixel",
+            )));
+        }
+        if self.tick_interval == 0 || self.sleep_tick_interval == Some(0) {
+            return Err(ManzarError::InvalidConfig(String::from(
+                "tick_interval and sleep_tick_interval must be at least 1 ms",
+            )));
+        }
+
+        if !(0.0..=1.0).contains(&self.smoothing) {
+            return Err(ManzarError::InvalidConfig(format!(
+                "smoothing must be in [0, 1], got {}",
+                self.smoothing
+            )));
+        }
+
+        if !(self.arrive_threshold.is_finite() && self.arrive_threshold >= 0.0) {
+            return Err(ManzarError::InvalidConfig(format!(
+                "arrive_threshold must be 0 or more, got {}",
+                self.arrive_threshold
+            )));
+        }
+
+        if let Some(max) = self
+            .max_chase_distance
+            .filter(|max| max.is_nan() || *max <= 0.0)
+        {
+            return Err(ManzarError::InvalidConfig(format!(
+                "max_chase_distance must be positive, got {}",
+                max
+            )));
+        }
+
+        if !(0.0..=1.0).contains(&self.fidget_chance) {
+            return Err(ManzarError::InvalidConfig(format!(
+                "fidget_chance must be in [0, 1], got {}",
+                self.fidget_chance
+            )));
+        }
+
+        if let Some(fidget) = self
+            .fidgets
+            .iter()
+            .find(|fidget| !fidget.weight.is_finite() || fidget.weight < 0.0)
+        {
+            return Err(ManzarError::InvalidConfig(format!(
+                "the weight of fidget {:?} must be 0 or more, got {}",
+                fidget.sprite, fidget.weight
+            )));
+        }
+        if !self.fidgets.is_empty() && self.fidgets.iter().all(|fidget| fidget.weight == 0.0) {
+            return Err(ManzarError::InvalidConfig(String::from(
+                "at least one of the fidgets needs a weight above 0",
+            )));
+        }
+
+        if !(0.0..1.0).contains(&self.throw_friction) {
+            return Err(ManzarError::InvalidConfig(format!(
+                "throw_friction must be in [0, 1), got {}",
+                self.throw_friction
+            )));
+        }
+
+        // a normalized vector always leans at least 1/sqrt(2) along some axis,
+        // so staying below that guarantees the cat always faces somewhere
+        let low = self.direction_threshold - self.direction_hysteresis;
+        let high = self.direction_threshold + self.direction_hysteresis;
+        if self.direction_hysteresis < 0.0 || low <= 0.0 || high >= std::f32::consts::FRAC_1_SQRT_2
+        {
+            return Err(ManzarError::InvalidConfig(format!(
+                "direction_threshold +/- direction_hysteresis must stay within (0, 0.707), got ({}, {})",
+                low, high
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_default_config_is_valid() {
+        assert!(ManzarConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn the_canvas_renderer_refuses_what_needs_the_element() {
+        for (draggable, trail, angry) in [
+            (true, false, false),
+            (false, true, false),
+            (false, false, true),
+        ] {
+            let config = ManzarConfig {
+                renderer: RendererKind::Canvas,
+                draggable,
+                trail,
+                angry,
+                ..ManzarConfig::default()
+            };
+            assert!(matches!(
+                config.validate(),
+                Err(ManzarError::InvalidConfig(_))
+            ));
+            let dom = ManzarConfig {
+                renderer: RendererKind::Dom,
+                ..config
+            };
+            assert!(dom.validate().is_ok());
+        }
+    }
+
+    #[test]
+    fn the_batch_renderer_refuses_angry_too() {
+        let config = ManzarConfig {
+            renderer: RendererKind::Batch,
+            angry: true,
+            ..ManzarConfig::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ManzarError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn a_negative_speed_is_rejected() {
+        let config = ManzarConfig {
+            speed: -1,
+            ..ManzarConfig::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ManzarError::InvalidConfig(_))
+        ));
+        let config = ManzarConfig {
+            speed: 0,
+            ..ManzarConfig::default()
+        };
+        assert!(config.validate().is_ok());
+    }
+}
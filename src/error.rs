@@ -0,0 +1,62 @@
+use std::fmt;
+use wasm_bindgen::{JsError, JsValue};
+
+/// Everything that can go wrong while setting up or driving the cat.
+/// Converts into a JS `Error` so callers get a readable message instead of a trap.
+#[derive(Debug)]
+pub enum ManzarError {
+    NoWindow,
+    NoDocument,
+    NoBody,
+    /// The options passed to `start` could not be used
+    InvalidConfig(String),
+    /// `confine_to` didn't match any element
+    NoContainer(String),
+    /// No sprite belongs to the given state name
+    UnknownSpriteState(String),
+    /// A DOM call threw, e.g. a style write rejected by the page
+    Dom(JsValue),
+    /// The render loop couldn't be scheduled, e.g. where `setInterval` isn't available
+    NoLoop(JsValue),
+}
+
+impl fmt::Display for ManzarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManzarError::NoWindow => write!(f, "no window exists"),
+            ManzarError::NoDocument => write!(f, "no document exists"),
+            ManzarError::NoBody => write!(f, "document does not have a body"),
+            ManzarError::InvalidConfig(reason) => write!(f, "invalid config: {}", reason),
+            ManzarError::NoContainer(selector) => {
+                write!(f, "no element matches confine_to selector `{}`", selector)
+            }
+            ManzarError::UnknownSpriteState(state) => write!(f, "unknown sprite state `{}`", state),
+            ManzarError::Dom(err) => match err.as_string() {
+                Some(msg) => write!(f, "DOM operation failed: {}", msg),
+                None => write!(f, "DOM operation failed: {:?}", err),
+            },
+            ManzarError::NoLoop(err) => match err.as_string() {
+                Some(msg) => write!(f, "could not schedule the render loop: {}", msg),
+                None => write!(f, "could not schedule the render loop: {:?}", err),
+            },
+        }
+    }
+}
+
+impl From<JsValue> for ManzarError {
+    fn from(err: JsValue) -> Self {
+        ManzarError::Dom(err)
+    }
+}
+
+impl From<serde_wasm_bindgen::Error> for ManzarError {
+    fn from(err: serde_wasm_bindgen::Error) -> Self {
+        ManzarError::InvalidConfig(err.to_string())
+    }
+}
+
+impl From<ManzarError> for JsValue {
+    fn from(err: ManzarError) -> Self {
+        JsError::new(&format!("manzar: {}", err)).into()
+    }
+}
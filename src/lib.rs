@@ -1,242 +1,1431 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    rc::{Rc, Weak},
+};
 use wasm_bindgen::prelude::*;
-use web_sys::{HtmlElement, MouseEvent};
+use web_sys::{CustomEvent, Document, HtmlElement, HtmlImageElement, PointerEvent, Window};
 
-// (x, y)
-#[derive(Clone)]
-struct Point(i32, i32);
+mod behavior;
+mod builder;
+mod clock;
+mod config;
+mod error;
+mod listeners;
+mod persist;
+mod render;
+mod rng;
+mod sprites;
+mod trail;
 
-#[derive(Clone)]
-enum AnimationDuration {
-    Infinite,
-    Definite(u32),
+pub use behavior::Behavior;
+use behavior::{commit_axis, Bounds, CatState, HeldKeys, IdleAction};
+pub use builder::ManzarBuilder;
+pub use clock::{Clock, ManualClock, WebClock};
+pub use config::{
+    AnimationSpeeds, Corner, Fidget, FollowerOptions, Home, IdleStage, ManzarConfig, NamedLayout,
+    Personality, PositionRounding, ScratchFrames, SheetLayout,
+};
+pub use error::ManzarError;
+use listeners::{Input, Inputs, Listener};
+pub use render::RendererKind;
+use render::{CanvasRenderer, DomRenderer, Renderer};
+use rng::Rng;
+use sprites::SpriteLayout;
+pub use sprites::{
+    Animation, AnimationDuration, CardinalSprites, ManzarSprites, OrdinalSprites, Point,
+    ScratchSprites, Sprite,
+};
+use trail::Trail;
+
+// A gap between ticks longer than this many intervals means the timer was throttled
+const MAX_TICK_GAP: f64 = 3.0;
+// Only pointer movement this recent counts towards the throw velocity, in ms
+const THROW_WINDOW: f64 = 100.0;
+// Fastest a thrown cat can fly, in pixels per tick
+const MAX_THROW_SPEED: f32 = 80.0;
+// Ticks without movement before the position is written to storage
+const PERSIST_DELAY: u32 = 5;
+// Downward acceleration in gravity mode, in pixels per tick per tick
+const GRAVITY: f32 = 3.0;
+// In face_only mode, a pointer that hasn't moved for this long is resting, in ms
+const POINTER_REST: f64 = 300.0;
+// Share of the gap to a new speed closed every tick, see `Manzar::set_speed`
+const SPEED_RAMP: f32 = 0.3;
+// With `look_around`, ticks idle before the cat looks around, and ticks it looks each way
+const LOOK_AROUND_AFTER: u32 = 10;
+const LOOK_TICKS: u32 = 5;
+// With `intro`, the stops of the little tour after spawning, relative to the start position
+const INTRO_TOUR: [(i32, i32); 4] = [(96, 0), (96, 64), (0, 64), (0, 0)];
+
+thread_local! {
+    // every cat until it is torn down, as its loop only holds on to it weakly
+    static RUNNING: RefCell<Vec<Rc<RefCell<ManzarState>>>> = RefCell::default();
 }
 
-#[derive(Clone)]
-struct Animation {
-    states: Vec<Point>,
-    duration: AnimationDuration,
-    speed: u32,
+struct AnimationState {
+    sprite: Sprite,
+    // what the sprite was picked as, which another sprite with the same frames isn't
+    kind: SpriteKind,
+    // ticks played, which is what definite durations count
+    frame: u32,
+    // clock time the animation was started at, which picks the frame shown
+    started: f64,
 }
 
-impl Animation {
-    fn is_infinite(&self) -> bool {
-        match self.duration {
-            AnimationDuration::Infinite => true,
-            _ => false,
-        }
-    }
+/// The sprites that hand back to something other than idle once they are over
+#[derive(Clone, Copy, PartialEq)]
+enum SpriteKind {
+    Plain,
+    // leads into sleep
+    Yawn,
+    // part of being idle, so it doesn't restart the idle cycle
+    Fidget,
 }
 
-#[derive(Clone)]
-enum Sprite {
-    Static(Point),
-    Animated(Animation),
+/// Recent clicks on the cat and how long it stays angry about them
+#[derive(Default)]
+struct AngryState {
+    // timestamps of recent clicks, in ms
+    clicks: VecDeque<f64>,
+    // ticks left until the cat calms down
+    cooldown: u32,
 }
 
-#[derive(Clone)]
-struct CardinalSprites {
-    n: Sprite,
-    e: Sprite,
-    s: Sprite,
-    w: Sprite,
+/// The cat being held by the pointer, or flying after being thrown
+#[derive(Default)]
+struct DragState {
+    active: bool,
+    // recent (timestamp, x, y) pointer samples
+    samples: VecDeque<(f64, i32, i32)>,
+    // pixels per tick
+    velocity: (f32, f32),
 }
 
+/// Another cat this one trails behind instead of chasing the cursor
+struct Follow {
+    // weak so a follower doesn't keep its leader's state alive
+    leader: Weak<RefCell<ManzarState>>,
+    // where to stand relative to the leader, in pixels
+    offset: (i32, i32),
+    // see `FollowerOptions::lag`
+    lag: f32,
+}
+
+/// A clicked spot a `curious` cat is on its way to, or sniffing at
 #[derive(Clone)]
-struct OrdinalSprites {
-    ne: Sprite,
-    se: Sprite,
-    sw: Sprite,
-    nw: Sprite,
+struct Detour {
+    spot: Point,
+    // ticks of sniffing left, once the cat is there
+    sniffing: Option<u32>,
 }
 
-// When the cat is scratching a page wall
-struct ScratchSprites {
-    cat: Sprite,
-    cardinal: CardinalSprites,
+/// Something for the cat to chase instead of the cursor, see `Manzar::add_target`
+enum Target {
+    Point(Point),
+    // chased to the center of its box, wherever it moves
+    Element(HtmlElement),
 }
 
-struct ManzarSprites {
-    idle: Sprite,
-    alert: Sprite,
-    tired: Sprite,
-    sleeping: Sprite,
-    cardinal: CardinalSprites,
-    ordinal: OrdinalSprites,
-    scratch: ScratchSprites,
+/// Something announced on the cat's element, see `ManzarState::set_state`
+#[derive(Clone, Copy, PartialEq)]
+enum CatEvent {
+    StateChange(CatState),
+    Sleep,
+    Wake,
 }
 
-struct AnimationState {
-    sprite: Sprite,
-    frame: u32,
+impl CatEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            CatEvent::StateChange(_) => "statechange",
+            CatEvent::Sleep => "sleep",
+            CatEvent::Wake => "wake",
+        }
+    }
+}
+
+/// A JS callback run every `every` rendered ticks, see `Manzar::on_heartbeat`
+struct Heartbeat {
+    callback: js_sys::Function,
+    every: u32,
 }
 
 struct IdleState {
     timeout: u32,
     frame: u32,
     buffer: u32,
+    // whether the next look around starts towards the west
+    look_west_first: bool,
 }
 
 struct ManzarState {
     element: HtmlElement,
+    renderer: Box<dyn Renderer>,
+    config: ManzarConfig,
     sprites: ManzarSprites,
     mouse: Point,
+    // where the pointer was last seen moving to and when, see `on_mouse_move`
+    last_pointer: Point,
+    last_pointer_move: f64,
+    // pointer moves counted by `listeners` that this cat has caught up on
+    pointer_moves: u64,
+    // page events queued up by `listeners` for the next tick
+    inputs: Inputs,
     cat: Point,
-    speed: i32,
-    frame: u32,
+    // `cat` with the sub-pixel movement that rounding drops, see `glide_to`
+    position: (f32, f32),
+    // current pixels per tick, easing towards `target_speed`
+    speed: f32,
+    target_speed: i32,
+    // ticks towards the next scratch, wrapped at `scratch_frequency` so it can't overflow
+    scratch_frame: u32,
     animation: AnimationState,
     idle: IdleState,
-    window_size: (i32, i32),
+    // refreshed every tick, see `update_bounds`
+    bounds: Bounds,
+    trail: Trail,
+    // url of the sheet currently shown, see `apply_sheet`
+    sheet: String,
+    // speech bubble shown by `say`, removed by its timer
+    bubble: Option<HtmlElement>,
+    state: CatState,
+    // committed facing as (x, y): x is 1 for west, y is 1 for north
+    heading: (i8, i8),
+    // x of the last heading that pointed west or east, which resting sprites face
+    last_horizontal_direction: i8,
+    // stopped through the handle
+    paused: bool,
+    // the tab is in the background
+    hidden: bool,
+    // taken off the page through the handle
+    visible: bool,
+    // started with `start_hidden` and not brought in yet, see `reveal`
+    awaiting_reveal: bool,
+    // holding still while the context menu is open
+    frozen: bool,
+    // waiting for the sheet to load to work out the tile size, see `measure_sheet`
+    measuring: bool,
+    // columns and rows of the sheet, when they are known, to check new sprites against
+    grid: Option<(u32, u32)>,
+    // the pointer is inside the window
+    pointer_present: bool,
+    // walking back to `home` to sleep there
+    homing: bool,
+    // stops of the intro tour still ahead, the next one first
+    intro: VecDeque<Point>,
+    // the click being investigated with `curious`
+    detour: Option<Detour>,
+    keys: HeldKeys,
+    drag: DragState,
+    angry: AngryState,
+    // downward speed in gravity mode, pixels per tick
+    fall_speed: f32,
+    // ticks until the cat may scratch again, see `scratch_cooldown`
+    scratch_wait: u32,
+    // id of the render loop's setInterval, until it is stopped
+    interval: Option<i32>,
+    // the callback it runs, kept to reschedule it at another rate until teardown
+    on_tick: Option<Closure<dyn FnMut()>>,
+    // the cat's own listeners for clicks and dragging, taken off at teardown
+    element_listeners: Vec<Listener>,
+    // ms between ticks the loop currently runs at
+    tick_rate: u32,
+    // clock time of the last tick that wasn't skipped, see `on_schedule`
+    last_tick: f64,
+    // ticks of the poof out left before a destroyed cat is removed
+    leaving: Option<u32>,
+    // ticks left until the moved cat's position is saved, if it is waiting to be
+    pending_save: Option<u32>,
+    follow: Option<Follow>,
+    heartbeat: Option<Heartbeat>,
+    // ticks rendered so far, and the one the heartbeat last ran on
+    ticks: u64,
+    last_beat: u64,
+    // the nearest of these is chased instead of the cursor, if there are any
+    targets: Vec<Target>,
+    // announced once the state is released again, see `Manzar::run`
+    events: Vec<CatEvent>,
+    rng: Rng,
+    clock: Rc<dyn Clock>,
+    #[cfg(feature = "debug")]
+    debug_hud: Option<HtmlElement>,
 }
 
 impl ManzarState {
-    fn on_mouse_down(&mut self, event: MouseEvent) {
-        let x = event.client_x();
-        let y = event.client_y();
+    /// The state of a cat just spawned at `start`, drawn by `renderer`. It is marked
+    /// as in the foreground and with no sheet grid checked yet, which is up to the caller.
+    fn new(
+        element: HtmlElement,
+        renderer: Box<dyn Renderer>,
+        config: ManzarConfig,
+        sprites: ManzarSprites,
+        (start_x, start_y): (i32, i32),
+        bounds: Bounds,
+        clock: Rc<dyn Clock>,
+    ) -> Self {
+        // the tour is the cat's own idea, not for cats someone else steers
+        let intro = if config.intro
+            && config.behavior == Behavior::Chase
+            && !config.keyboard
+            && !config.face_only
+        {
+            INTRO_TOUR
+                .iter()
+                .map(|&(dx, dy)| Point(start_x + dx, start_y + dy))
+                .collect()
+        } else {
+            VecDeque::new()
+        };
+        // the first ticks play the poof in, which then hands over to idle
+        let first = if config.poof {
+            sprites.poof_in.clone()
+        } else {
+            sprites.idle.clone()
+        };
+        let rng = config.seed.map_or_else(Rng::from_entropy, Rng::new);
+        Self {
+            element,
+            renderer,
+            sprites,
+            mouse: Point(start_x, start_y),
+            last_pointer: Point(start_x, start_y),
+            last_pointer_move: 0.0,
+            pointer_moves: listeners::pointer_moves(),
+            inputs: Inputs::default(),
+            cat: Point(start_x, start_y),
+            position: (start_x as f32, start_y as f32),
+            speed: config.speed as f32,
+            target_speed: config.speed,
+            scratch_frame: 0,
+            animation: AnimationState {
+                sprite: first,
+                kind: SpriteKind::Plain,
+                frame: 0,
+                started: 0.0,
+            },
+            idle: IdleState {
+                timeout: config.idle_timeout,
+                frame: 0,
+                buffer: 0,
+                look_west_first: false,
+            },
+            bounds,
+            trail: Trail::new(config.trail_length),
+            bubble: None,
+            sheet: config.sheet_url.clone(),
+            state: CatState::Idle,
+            heading: (0, 0),
+            last_horizontal_direction: 1,
+            paused: false,
+            hidden: false,
+            visible: !config.start_hidden,
+            awaiting_reveal: config.start_hidden,
+            frozen: false,
+            measuring: config.sheet_grid.is_some(),
+            grid: None,
+            pointer_present: true,
+            homing: false,
+            intro,
+            detour: None,
+            keys: HeldKeys::default(),
+            drag: DragState::default(),
+            angry: AngryState::default(),
+            fall_speed: 0.0,
+            scratch_wait: 0,
+            interval: None,
+            on_tick: None,
+            element_listeners: Vec::new(),
+            tick_rate: config.tick_interval,
+            last_tick: clock.now(),
+            leaving: None,
+            pending_save: None,
+            follow: None,
+            heartbeat: None,
+            ticks: 0,
+            last_beat: 0,
+            targets: Vec::new(),
+            events: Vec::new(),
+            rng,
+            clock,
+            #[cfg(feature = "debug")]
+            debug_hud: None,
+            config,
+        }
+    }
+
+    /// Work through what happened on the page since the last tick, see `listeners`
+    fn handle_inputs(&mut self) -> Result<(), ManzarError> {
+        // only the latest of the pointer moves matters
+        if let Some((moves, pointer)) = listeners::pointer_since(self.pointer_moves) {
+            self.pointer_moves = moves;
+            self.on_mouse_move(pointer)?;
+        }
+        let inputs: Vec<Input> = self.inputs.borrow_mut().drain(..).collect();
+        let freeze = self.config.freeze_on_context_menu;
+        for input in inputs {
+            match input {
+                Input::MouseDown { x, y, button } => self.on_mouse_down(x, y, button)?,
+                Input::PointerPresent(present) => self.pointer_present = present,
+                Input::Hidden(hidden) => self.hidden = hidden,
+                Input::Key { key, pressed } => {
+                    if self.config.keyboard {
+                        self.keys.set(&key, pressed);
+                    }
+                    // a key press closes the context menu, see `on_mouse_down` for clicks
+                    if pressed && freeze {
+                        self.frozen = false;
+                    }
+                }
+                Input::ContextMenu => self.frozen |= freeze,
+                Input::Scroll => self.on_scroll()?,
+                Input::PointerDown { x, y, at } => self.on_pointer_down(x, y, at)?,
+                Input::PointerMove { x, y, at } => self.on_pointer_move(x, y, at)?,
+                Input::PointerUp => self.on_pointer_up(),
+                Input::CatClick { at } => self.on_cat_click(at),
+                Input::SheetMeasured(size) => self.on_sheet_measured(size)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn on_mouse_down(&mut self, x: i32, y: i32, button: i16) -> Result<(), ManzarError> {
+        // the right click opening the context menu doesn't count as the click closing it
+        if button == 0 {
+            self.frozen = false;
+        }
         self.mouse = Point(x, y);
+        self.homing = false;
+        self.intro.clear();
+        // the cat isn't the one picking its target in these modes
+        let curious = self.config.curious
+            && self.config.behavior == Behavior::Chase
+            && self.follow.is_none()
+            && !self.config.keyboard
+            && !self.config.face_only;
+        if curious {
+            // another click while on the way, or sniffing, just moves the spot
+            self.detour = Some(Detour {
+                spot: Point(x, y),
+                sniffing: None,
+            });
+        }
+        self.wake_loop()
+    }
+
+    /// Any real pointer movement means someone is still around, so the cat
+    /// doesn't doze off right under a cursor that keeps wiggling
+    fn on_mouse_move(&mut self, pointer: Point) -> Result<(), ManzarError> {
+        let deadzone = self.config.activity_deadzone as f32;
+        if behavior::distance(&pointer, &self.last_pointer) <= deadzone {
+            return Ok(());
+        }
+        if self.config.reveal_on_move {
+            self.reveal()?;
+        }
+        if !self.intro.is_empty() {
+            // someone is around, so the tour is cut short and the cat goes to them
+            self.intro.clear();
+            self.mouse = pointer.clone();
+        }
+        self.wake_loop()?;
+        self.last_pointer = pointer;
+        self.last_pointer_move = self.clock.now();
+        // the alert buffer is left alone, it is re-armed by the idle branch anyway
+        // and clearing it mid-startle would cut the alert short
+        self.idle.frame = 0;
+        Ok(())
+    }
+
+    /// Bring in a cat started with `start_hidden`, where it was placed on spawn
+    fn reveal(&mut self) -> Result<(), ManzarError> {
+        if !self.awaiting_reveal {
+            return Ok(());
+        }
+        self.awaiting_reveal = false;
+        // clicks made while it was hidden are stale, so it starts out sitting still
+        self.mouse = self.cat.clone();
+        self.detour = None;
+        self.idle.frame = 0;
+        if self.config.poof {
+            self.animation.frame = 0;
+            self._set_sprite(&self.sprites.poof_in.clone())?;
+        }
+        self.visible = true;
+        self.renderer.set_visible(true)
     }
 
     fn get_cardinal_scratch_sprite(&self) -> &Sprite {
-        let cx = self.cat.0;
-        let cy = self.cat.1;
-        let x = self.window_size.0;
-        let y = self.window_size.1;
-        let margin = 10;
-
-        let mut map = HashMap::new();
-        let scratch = &self.sprites.scratch;
-        map.insert(cx, &scratch.cardinal.w);
-        map.insert(cy, &scratch.cardinal.n);
-        map.insert(x - cx, &scratch.cardinal.e);
-        map.insert(y - cy, &scratch.cardinal.s);
-        let mut items: Vec<&i32> = map.keys().filter(|d| **d < margin).collect();
-        if items.is_empty() {
-            &scratch.cat
-        } else {
-            items.sort();
-            map.get(items[0]).unwrap()
+        let gaps = self.bounds.gaps(
+            self.cat.0,
+            self.cat.1,
+            self.config.anchor(),
+            self.config.size(),
+        );
+        self.sprites
+            .scratching(gaps, self.config.scratch_margin as i32)
+    }
+
+    /// Record a state change, to be announced on the cat's element as a
+    /// `<event_prefix>:statechange` event with `detail = { id, state }`.
+    /// Falling asleep and waking up are also announced as `<event_prefix>:sleep`
+    /// and `<event_prefix>:wake`. Nothing is announced if the state did not actually change.
+    fn set_state(&mut self, state: CatState) -> Result<(), ManzarError> {
+        if self.state == state {
+            return Ok(());
+        }
+        let previous = std::mem::replace(&mut self.state, state);
+        self.events.push(CatEvent::StateChange(state));
+        if state == CatState::Sleeping {
+            self.events.push(CatEvent::Sleep);
+        } else if previous == CatState::Sleeping {
+            self.events.push(CatEvent::Wake);
+        }
+        Ok(())
+    }
+
+    /// Runs once per interval tick
+    fn tick(&mut self) -> Result<(), ManzarError> {
+        // the last few ticks of a destroyed cat, even a paused one
+        if let Some(ticks_left) = self.leaving {
+            return self.poof_out(ticks_left);
+        }
+        // every manual step is a tick of its own
+        if !self.config.manual_step && !self.on_schedule() {
+            return Ok(());
+        }
+        // caught up on even while the cat holds still, as that is what the
+        // inputs may change, e.g. by bringing the tab back or revealing the cat
+        self.handle_inputs()?;
+        // copies fade away even behind a cat that is holding still
+        self.trail.expire(self.clock.now())?;
+        // ticks are dropped rather than queued, so nothing is caught up on resume
+        if self.paused || self.hidden || !self.visible || self.frozen || self.measuring {
+            return Ok(());
+        }
+        self.update_bounds()?;
+        self.follow_leader();
+        self.chase_nearest_target();
+        self.render()?;
+        self.ticks += 1;
+        if let Some(rate) = self.config.sleep_tick_interval {
+            // a sleeping cat barely changes, so it doesn't need the full rate. Whatever
+            // woke it up, it's back to the normal rate from here on.
+            if self.state == CatState::Sleeping {
+                self.set_tick_rate(rate)?;
+            } else {
+                self.wake_loop()?;
+            }
         }
+        self.save_position()?;
+        #[cfg(feature = "debug")]
+        self.update_debug_hud()?;
+        Ok(())
     }
 
-    fn render(&mut self) {
-        self.frame = self.frame + 1;
+    /// The heartbeat callback and its argument, if one is due after this tick. It is
+    /// called by the loop once the state is no longer borrowed, so the callback is
+    /// free to use the handle.
+    fn due_heartbeat(&mut self) -> Result<Option<(js_sys::Function, js_sys::Object)>, ManzarError> {
+        let Some(heartbeat) = &self.heartbeat else {
+            return Ok(None);
+        };
+        if self.ticks == self.last_beat || !self.ticks.is_multiple_of(heartbeat.every as u64) {
+            return Ok(None);
+        }
+        self.last_beat = self.ticks;
+        let detail = js_sys::Object::new();
+        js_sys::Reflect::set(&detail, &"tick".into(), &(self.ticks as f64).into())?;
+        js_sys::Reflect::set(&detail, &"x".into(), &self.cat.0.into())?;
+        js_sys::Reflect::set(&detail, &"y".into(), &self.cat.1.into())?;
+        js_sys::Reflect::set(&detail, &"state".into(), &self.state.name().into())?;
+        Ok(Some((heartbeat.callback.clone(), detail)))
+    }
+
+    /// Guard against throttled timers. Background tabs fire the ticks they owe in a
+    /// burst once they are back, which would make the cat lurch, so ticks arriving
+    /// right on the heels of the previous one are skipped. After a long gap the
+    /// animation carries on where it was instead of jumping ahead.
+    fn on_schedule(&mut self) -> bool {
+        let now = self.clock.now();
+        let gap = now - self.last_tick;
+        // measured against the current rate, which is slower while asleep
+        let rate = self.tick_rate as f64;
+        if gap < rate / 2.0 {
+            return false;
+        }
+        self.last_tick = now;
+        if gap > MAX_TICK_GAP * rate {
+            self.animation.started += gap - rate;
+        }
+        true
+    }
+
+    /// Reschedule the render loop to tick every `rate` ms. Does nothing for a
+    /// stopped loop, or one already running at that rate.
+    fn set_tick_rate(&mut self, rate: u32) -> Result<(), ManzarError> {
+        let (Some(interval), Some(on_tick)) = (self.interval, &self.on_tick) else {
+            return Ok(());
+        };
+        if rate == self.tick_rate {
+            return Ok(());
+        }
+        let window = web_sys::window().ok_or(ManzarError::NoWindow)?;
+        window.clear_interval_with_handle(interval);
+        self.interval = Some(
+            window.set_interval_with_callback_and_timeout_and_arguments_0(
+                on_tick.as_ref().unchecked_ref(),
+                rate as i32,
+            )?,
+        );
+        self.tick_rate = rate;
+        Ok(())
+    }
+
+    /// Go back to the normal tick rate after `sleep_tick_interval` slowed it down
+    fn wake_loop(&mut self) -> Result<(), ManzarError> {
+        self.set_tick_rate(self.config.tick_interval)
+    }
+
+    /// Ease the speed towards the one last asked for, so speed changes don't jerk
+    fn ramp_speed(&mut self) {
+        let target = self.target_speed as f32;
+        self.speed += (target - self.speed) * SPEED_RAMP;
+        if (target - self.speed).abs() < 0.5 {
+            self.speed = target;
+        }
+    }
+
+    /// Aim a follower at its spot next to the leader. The spot is clamped so a
+    /// leader near an edge doesn't send the follower running into it forever.
+    fn follow_leader(&mut self) {
+        let Some(follow) = &self.follow else {
+            return;
+        };
+        // a leader that is gone leaves the follower at its last target
+        let Some(leader) = follow.leader.upgrade() else {
+            return;
+        };
+        let leader = leader.borrow().cat.clone();
+        let (x, y) = self.bounds.clamp(
+            leader.0 + follow.offset.0,
+            leader.1 + follow.offset.1,
+            self.config.anchor(),
+            self.config.size(),
+        );
+        let spot = Point(x, y);
+        // a follower that is already on its way keeps up with the spot
+        if self.state != CatState::Moving && behavior::distance(&self.cat, &spot) <= follow.lag {
+            return;
+        }
+        self.mouse = spot;
+    }
+
+    /// Aim at whichever registered target is closest right now. Elements removed
+    /// from the page are dropped from the list.
+    fn chase_nearest_target(&mut self) {
+        self.targets.retain(|target| match target {
+            Target::Element(element) => element.is_connected(),
+            Target::Point(_) => true,
+        });
+        // a follower sticks with its leader
+        if self.follow.is_some() {
+            return;
+        }
+        let nearest = self
+            .targets
+            .iter()
+            .map(|target| match target {
+                Target::Point(point) => point.clone(),
+                Target::Element(element) => {
+                    let rect = element.get_bounding_client_rect();
+                    Point(
+                        (rect.left() + rect.width() / 2.0).round() as i32,
+                        (rect.top() + rect.height() / 2.0).round() as i32,
+                    )
+                }
+            })
+            .min_by(|a, b| {
+                behavior::distance(&self.cat, a).total_cmp(&behavior::distance(&self.cat, b))
+            });
+        if let Some(point) = nearest {
+            self.mouse = point;
+        }
+    }
+
+    /// Switch to frames of `tile_size` pixels, keeping the cat's anchor where it is
+    fn set_tile_size(&mut self, tile_size: i32) -> Result<(), ManzarError> {
+        self.config.tile_size = tile_size;
+        self.renderer.set_tile_size(tile_size, self.config.size())?;
+        self.move_to(self.cat.0, self.cat.1)
+    }
+
+    fn stop(&mut self) -> Result<(), ManzarError> {
+        if let Some(interval) = self.interval.take() {
+            web_sys::window()
+                .ok_or(ManzarError::NoWindow)?
+                .clear_interval_with_handle(interval);
+        }
+        Ok(())
+    }
+
+    /// Stop the loop and remove everything the cat put on the page
+    fn teardown(&mut self) -> Result<(), ManzarError> {
+        self.stop()?;
+        // the loop may be the one tearing down, wasm-bindgen frees it once it returns
+        self.on_tick = None;
+        for listener in std::mem::take(&mut self.element_listeners) {
+            listener.detach()?;
+        }
+        listeners::unregister(&self.inputs)?;
+        // whoever tears the cat down holds on to it, so this isn't the last reference
+        RUNNING.with(|running| {
+            running
+                .borrow_mut()
+                .retain(|state| !std::ptr::eq(state.as_ptr(), self))
+        });
+        self.leaving = None;
+        self.visible = false;
+        self.trail.remove();
+        if let Some(bubble) = self.bubble.take() {
+            bubble.remove();
+        }
+        #[cfg(feature = "debug")]
+        if let Some(hud) = self.debug_hud.take() {
+            hud.remove();
+        }
+        self.renderer.remove()?;
+        // with the dom renderer this is the element just removed, possibly after a fade
+        if self.config.renderer != RendererKind::Dom {
+            self.element.remove();
+        }
+        Ok(())
+    }
+
+    /// Play the next frame of the poof out, tearing down once it is over
+    fn poof_out(&mut self, ticks_left: u32) -> Result<(), ManzarError> {
+        // a cat that can't be seen has nothing to show
+        if ticks_left == 0 || !self.visible || self.hidden {
+            return self.teardown();
+        }
+        self.leaving = Some(ticks_left - 1);
+        self._set_sprite(&self.sprites.poof_out.clone())
+    }
+
+    /// Write the position to storage once the cat has stopped moving for a bit
+    fn save_position(&mut self) -> Result<(), ManzarError> {
+        match self.pending_save {
+            Some(0) => {
+                self.pending_save = None;
+                persist::save(&self.config.storage_key, self.cat.0, self.cat.1)
+            }
+            Some(ticks) => {
+                self.pending_save = Some(ticks - 1);
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Re-read the area the cat may move in. The confining element and the window are
+    /// measured every tick since either can be resized or scrolled at any time.
+    fn update_bounds(&mut self) -> Result<(), ManzarError> {
+        self.bounds = match &self.config.confine_to {
+            Some(selector) => {
+                let document = self
+                    .element
+                    .owner_document()
+                    .ok_or(ManzarError::NoDocument)?;
+                measure_container(&document, selector)?
+            }
+            None => viewport_bounds(&web_sys::window().ok_or(ManzarError::NoWindow)?)?,
+        };
+        Ok(())
+    }
+
+    fn render(&mut self) -> Result<(), ManzarError> {
+        // clamping into a collapsed container would pile the cat up in its corner,
+        // so it holds still until the container has a size again
+        if self.bounds.is_empty() {
+            return Ok(());
+        }
+        self.scratch_frame = (self.scratch_frame + 1) % self.config.scratch_frequency.max(1);
+        self.scratch_wait = self.scratch_wait.saturating_sub(1);
+        self.ramp_speed();
+
+        if self.drag.active {
+            // dangling from the pointer, which moves the cat from its own handler
+            self.idle.frame = 0;
+            self.set_state(CatState::Alert)?;
+            return self.set_sprite(&self.sprites.alert.clone());
+        }
+        if self.angry.cooldown > 0 {
+            self.angry.cooldown -= 1;
+            self.idle.frame = 0;
+            self.set_state(CatState::Angry)?;
+            return self.set_sprite(&self.sprites.angry.clone());
+        }
+        let (vx, vy) = self.drag.velocity;
+        if vx.abs() >= 0.5 || vy.abs() >= 0.5 {
+            return self.fly();
+        }
+
+        self.next_intro_stop();
+        if self.investigate()? {
+            return Ok(());
+        }
+
+        if self.config.keyboard {
+            let heading = self.keys.heading();
+            if heading != (0, 0) {
+                return self.walk_keyboard(heading);
+            }
+            // nothing held, so stand still and let the idle logic below run
+            self.mouse = self.cat.clone();
+        }
+
+        if self.config.face_only {
+            // look at the pointer while it moves, and rest once it stops
+            let moving = self.clock.now() - self.last_pointer_move < POINTER_REST;
+            self.mouse = if moving {
+                self.last_pointer.clone()
+            } else {
+                self.cat.clone()
+            };
+        }
+
+        let dist = behavior::distance(&self.cat, &self.mouse);
 
-        let diff_x = self.cat.0 - self.mouse.0;
-        let diff_y = self.cat.1 - self.mouse.1;
-        let dist = ((diff_x.pow(2) + diff_y.pow(2)) as f32).abs().sqrt();
+        let speed = self.speed;
 
-        let speed = self.speed as f32;
+        // in gravity mode the cat only leaps for targets within reach,
+        // and drops to the ground whenever it isn't chasing
+        let out_of_reach = self.config.gravity && dist > self.config.leap_range;
+        // a cursor too far away isn't worth the run, the cat waits for it to come back
+        let too_far = self.config.max_chase_distance.is_some_and(|max| dist > max);
+        // the last known position goes stale once the pointer leaves the window,
+        // so settle down until it comes back. Followers chase their leader instead,
+        // and registered targets don't need the pointer either.
+        let pointer_gone = !self.pointer_present
+            && self.follow.is_none()
+            && self.targets.is_empty()
+            && !self.homing
+            && self.intro.is_empty()
+            && self.detour.is_none();
+        let fleeing = self.config.behavior == Behavior::Flee;
+        let settled = behavior::settled(
+            dist,
+            speed,
+            self.config.arrive_threshold,
+            Some(self.config.flee_range).filter(|_| fleeing),
+        );
+        // with no way to tell where to head, e.g. on the first tick when the cat spawns
+        // right on its target, it sits down instead of freezing on whatever frame it shows.
+        // This also covers a speed of 0 or less, which `settled` doesn't catch.
+        let on_target = dist == 0.0;
+        let resting = settled || on_target || out_of_reach || too_far || pointer_gone;
+        if self.config.gravity && resting && self.fall()? {
+            return Ok(());
+        }
 
         // Idle Logic (cat close to mouse)
-        if dist < speed {
+        if resting {
+            self.renderer.set_mirrored(self.rest_mirrored())?;
+            if self.homing {
+                // made it home, where it lies straight down to sleep
+                self.homing = false;
+                let asleep = self.config.idle_stages.iter().find(|stage| stage.asleep);
+                if let Some(stage) = asleep {
+                    self.idle.frame = (self.idle.timeout + stage.after).saturating_sub(1);
+                }
+            }
             if self.idle.frame == 0 {
-                self.set_sprite(&self.sprites.idle.clone());
+                self.set_state(CatState::Idle)?;
+                self.set_sprite(&self.sprites.idle.clone())?;
                 self.idle.frame = 1;
             } else {
-                self.idle.frame = self.idle.frame + 1;
+                // nothing changes once the last stage is reached, so stop counting there
+                let last_stage = self
+                    .config
+                    .idle_stages
+                    .last()
+                    .map_or(0, |stage| stage.after);
+                let wound_down = self.idle.timeout.saturating_add(last_stage);
+                self.idle.frame = self.idle.frame.saturating_add(1).min(wound_down);
                 if self.idle.frame >= self.idle.timeout {
-                    let diff = self.idle.frame - self.idle.timeout;
-
-                    // change below to adjust scratch frequency
-                    // make sure it's > 100
-                    let scratch_flag = self.frame % 101 == 0;
-
-                    if diff > 40 {
-                        self.set_sprite(&self.sprites.sleeping.clone());
-                    } else if scratch_flag {
-                        self.set_sprite(&self.get_cardinal_scratch_sprite().clone());
-                    } else if (20..40).contains(&diff) {
-                        self.set_sprite(&self.sprites.tired.clone());
-                    } else {
-                        self.set_sprite(&self.animation.sprite.clone());
+                    let scratch_due = self.config.scratch_enabled
+                        && self
+                            .scratch_frame
+                            .is_multiple_of(self.config.scratch_frequency)
+                        && self.scratch_wait == 0;
+                    let action = behavior::idle_action(
+                        self.idle.frame - self.idle.timeout,
+                        scratch_due,
+                        self.config.yawn,
+                        &self.config.idle_stages,
+                    );
+                    match action {
+                        IdleAction::Stage(index)
+                            if self.config.idle_stages[index].asleep && self.head_home() =>
+                        {
+                            return Ok(());
+                        }
+                        IdleAction::Stage(index) => {
+                            let stage = &self.config.idle_stages[index];
+                            let state = if stage.asleep {
+                                CatState::Sleeping
+                            } else {
+                                CatState::Tired
+                            };
+                            let sprite = self.stage_sprite(index);
+                            self.set_state(state)?;
+                            self.set_sprite(&sprite)?;
+                        }
+                        IdleAction::Scratch => {
+                            let sprite = self.get_cardinal_scratch_sprite().clone();
+                            // counted from the start, so the cooldown begins once it's over
+                            self.scratch_wait =
+                                sprite.ticks().unwrap_or(0) + self.config.scratch_cooldown;
+                            self.set_state(CatState::Scratching)?;
+                            self.set_sprite(&sprite)?;
+                        }
+                        IdleAction::Yawn => {
+                            self.set_state(CatState::Yawning)?;
+                            self.set_sprite_as(&self.sprites.yawn.clone(), SpriteKind::Yawn)?;
+                        }
+                        IdleAction::Continue => {
+                            let kind = self.animation.kind;
+                            self.set_sprite_as(&self.animation.sprite.clone(), kind)?;
+                        }
                     }
+                } else if let Some((sprite, mirrored)) = self.look_around() {
+                    self.set_sprite(&sprite)?;
+                    self.renderer.set_mirrored(mirrored)?;
+                } else if self.animation.kind == SpriteKind::Fidget {
+                    // keep playing it until it hands back to the idle sprite
+                    self.set_sprite(&self.sprites.idle.clone())?;
+                } else if self.rng.chance(self.config.fidget_chance) {
+                    let fidget = self.pick_fidget();
+                    self.set_sprite_as(&fidget, SpriteKind::Fidget)?;
                 }
             }
             if self.idle.buffer == 0 {
-                // change below to adjust alert time
-                self.idle.buffer = 5;
+                self.idle.buffer = self.config.alert_ticks();
             }
-            return ();
+            return Ok(());
         }
 
         self.idle.frame = 0;
+        // yawns and fidgets are the definite animations that shouldn't hold the cat back
+        if self.animation.kind != SpriteKind::Plain {
+            self.animation.frame = 0;
+            self._set_sprite(&self.sprites.alert.clone())?;
+        }
         if self.idle.buffer > 0 {
-            self.idle.buffer = self.idle.buffer - 1;
-            self.set_sprite(&self.sprites.alert.clone());
-            return ();
+            self.idle.buffer -= 1;
+            self.renderer.set_mirrored(self.rest_mirrored())?;
+            self.set_state(CatState::Alert)?;
+            self.set_sprite(&self.sprites.alert.clone())?;
+            return Ok(());
+        }
+
+        // `on_target` sent a cat without a direction to the idle branch
+        let Some((dx, dy)) = behavior::direction(&self.cat, &self.mouse) else {
+            return Ok(());
+        };
+        // fleeing is chasing in reverse, down to the walking sprite it picks
+        let (dx, dy) = if fleeing { (-dx, -dy) } else { (dx, dy) };
+        // easing in only makes sense when closing in on the target. The committed
+        // heading below only picks the sprite, the step goes straight for the target.
+        let smoothing = if fleeing { 0.0 } else { self.config.smoothing };
+        let (x, y) = behavior::step(self.position, &self.mouse, (dx, dy), speed, smoothing);
+
+        let threshold = self.config.direction_threshold;
+        let band = self.config.direction_hysteresis;
+        self.heading = (
+            commit_axis(dx, self.heading.0, threshold, band),
+            commit_axis(dy, self.heading.1, threshold, band),
+        );
+
+        if fleeing && self.cornered(x, y) {
+            self.renderer.set_mirrored(self.rest_mirrored())?;
+            self.set_state(CatState::Cowering)?;
+            return self.set_sprite(&self.sprites.cower.clone());
         }
+        self.walk(x, y)
+    }
 
-        let cur_x = self.cat.0 as f32;
-        let cur_y = self.cat.1 as f32;
+    /// With `curious`, head for the clicked spot and sniff at it for `sniff_ticks`
+    /// once there, before going on to the pointer. Returns whether the cat sniffed
+    /// this tick.
+    fn investigate(&mut self) -> Result<bool, ManzarError> {
+        let Some(Detour { spot, sniffing }) = self.detour.clone() else {
+            return Ok(false);
+        };
+        let ticks_left = match sniffing {
+            Some(ticks) => ticks,
+            None => {
+                let dist = behavior::distance(&self.cat, &spot);
+                // arrived by the same measure as `settled` in `render`
+                if dist >= self.speed.max(1.0) && dist >= self.config.arrive_threshold {
+                    self.mouse = spot;
+                    return Ok(false);
+                }
+                self.config.sniff_ticks
+            }
+        };
+        if ticks_left == 0 {
+            self.detour = None;
+            self.mouse = self.last_pointer.clone();
+            return Ok(false);
+        }
+        self.detour = Some(Detour {
+            spot,
+            sniffing: Some(ticks_left - 1),
+        });
+        // starts idling afresh once the sniffing is over
+        self.idle.frame = 0;
+        self.renderer.set_mirrored(self.rest_mirrored())?;
+        self.set_state(CatState::Sniffing)?;
+        self.set_sprite(&self.sprites.sniff.clone())?;
+        Ok(true)
+    }
 
-        // Make sure distance is not 0 here! WASM will give you an unreadable error!
+    /// Aim at the next stop of the intro tour, moving on from the ones the cat has
+    /// reached. Stops are kept inside the bounds so the cat can get to all of them.
+    fn next_intro_stop(&mut self) {
+        while let Some(stop) = self.intro.front() {
+            let (x, y) =
+                self.bounds
+                    .clamp(stop.0, stop.1, self.config.anchor(), self.config.size());
+            let stop = Point(x, y);
+            // past the last stop the cat stays aimed at it, and settles down there
+            if behavior::distance(&self.cat, &stop) < self.speed.max(1.0) {
+                self.intro.pop_front();
+                continue;
+            }
+            self.mouse = stop;
+            return;
+        }
+    }
 
-        let dx = diff_x as f32 / dist;
-        let dy = diff_y as f32 / dist;
+    /// With `mirror`, the sheet is expected to draw the resting states facing west,
+    /// and they are mirrored to face east when that's where the cat last walked
+    fn rest_mirrored(&self) -> bool {
+        self.config.mirror && self.last_horizontal_direction == -1
+    }
 
-        let x = cur_x - dx * speed;
-        let y = cur_y - dy * speed;
+    /// Whether the bounds leave the cat no room at all to step towards (x, y)
+    fn cornered(&self, x: f32, y: f32) -> bool {
+        let target = (x.round() as i32, y.round() as i32);
+        let stepped =
+            self.bounds
+                .clamp(target.0, target.1, self.config.anchor(), self.config.size());
+        target != (self.cat.0, self.cat.1) && stepped == (self.cat.0, self.cat.1)
+    }
 
-        let mut direction = String::new();
+    /// Set off for `home` if the cat is about to fall asleep somewhere else.
+    /// Returns whether it did.
+    fn head_home(&mut self) -> bool {
+        // the cat isn't the one picking its target in these modes
+        let steered = self.follow.is_some()
+            || !self.targets.is_empty()
+            || self.config.keyboard
+            || self.config.face_only;
+        let Some(home) = self.config.home.as_ref().filter(|_| !steered) else {
+            return false;
+        };
+        let (x, y) = match *home {
+            Home::Point(x, y) => (x, y),
+            Home::Corner(corner) => match corner {
+                Corner::TopLeft => (self.bounds.left, self.bounds.top),
+                Corner::TopRight => (self.bounds.right, self.bounds.top),
+                Corner::BottomLeft => (self.bounds.left, self.bounds.bottom),
+                Corner::BottomRight => (self.bounds.right, self.bounds.bottom),
+            },
+        };
+        let home = self
+            .bounds
+            .clamp(x, y, self.config.anchor(), self.config.size());
+        let home = Point(home.0, home.1);
+        if behavior::distance(&self.cat, &home) < self.speed.max(1.0) {
+            return false;
+        }
+        self.mouse = home;
+        self.homing = true;
+        // sleepy enough to skip the startled look
+        self.idle.buffer = 0;
+        true
+    }
 
-        if dy > 0.5 {
-            direction.push('N')
-        } else if dy < -0.5 {
-            direction.push('S')
+    /// With `look_around`, the sprite to show while the idle cat glances one way and
+    /// then the other, and whether to mirror it. None outside of the glance.
+    fn look_around(&mut self) -> Option<(Sprite, bool)> {
+        if !self.config.look_around {
+            return None;
         }
+        let since = self.idle.frame.checked_sub(LOOK_AROUND_AFTER)?;
+        if since == 0 {
+            self.idle.look_west_first = self.rng.chance(0.5);
+        }
+        let first = since < LOOK_TICKS;
+        if since >= 2 * LOOK_TICKS {
+            // looking straight ahead again, which only needs doing once
+            return (since == 2 * LOOK_TICKS).then(|| (self.sprites.idle.clone(), false));
+        }
+        let direction = if first == self.idle.look_west_first {
+            "W"
+        } else {
+            "E"
+        };
+        // standing still, so the walk cycle is held on its first frame
+        let (sprite, mirrored) = self.sprites.walking(direction, self.config.mirror);
+        Some((sprite.still(), mirrored))
+    }
 
-        if dx > 0.5 {
-            direction.push('W')
-        } else if dx < -0.5 {
-            direction.push('E')
+    /// One of the configured fidgets, by weight, or the `fidget` sprite without any
+    fn pick_fidget(&mut self) -> Sprite {
+        if self.config.fidgets.is_empty() {
+            return self.sprites.fidget.clone();
         }
+        let weights: Vec<f32> = self.config.fidgets.iter().map(|f| f.weight).collect();
+        let index = self.rng.pick_weighted(&weights);
+        self.named_sprite(&self.config.fidgets[index].sprite.clone())
+    }
+
+    /// A sprite by the name `set_state_sprite` takes, looked up every time so that
+    /// replacing the sprite also changes what the config points at
+    fn named_sprite(&mut self, name: &str) -> Sprite {
+        // `with_stages` and `with_fidgets` checked every name at startup
+        self.sprites
+            .get_mut(name)
+            .cloned()
+            .unwrap_or_else(|| self.sprites.idle.clone())
+    }
 
-        let sprite = self.get_compass_sprites(&direction);
-        // let sprite = set[(self.frame % 2) as usize];
-        self.set_sprite(&sprite);
+    /// The sprite of the idle stage at `index`. Looked up by name every time, so
+    /// `set_state_sprite` also changes the stages showing that sprite.
+    fn stage_sprite(&mut self, index: usize) -> Sprite {
+        let name = self.config.idle_stages[index].sprite.clone();
+        self.named_sprite(&name)
+    }
+
+    /// Show the current heading's walking sprite and step towards (x, y),
+    /// unless a definite animation is still playing
+    fn walk(&mut self, x: f32, y: f32) -> Result<(), ManzarError> {
+        self.set_state(CatState::Moving)?;
+        if self.heading.0 != 0 {
+            self.last_horizontal_direction = self.heading.0;
+        }
+        let direction = behavior::compass(self.heading);
+        let (sprite, mirrored) = self.sprites.walking(direction, self.config.mirror);
+        self.set_sprite(&sprite.clone())?;
+        self.renderer.set_mirrored(mirrored)?;
         match &self.animation.sprite {
             Sprite::Static(_) => (),
             Sprite::Animated(anim) => {
                 if !anim.is_infinite() {
-                    return (); // Don't move if a definite animation is playing
+                    return Ok(()); // Don't move if a definite animation is playing
                 }
             }
         }
-        self.move_to(x.round() as i32, y.round() as i32);
+        if self.config.face_only {
+            return Ok(());
+        }
+        self.glide_to(x, y)
+    }
+
+    /// Step in the direction of the held keys
+    fn walk_keyboard(&mut self, heading: (i8, i8)) -> Result<(), ManzarError> {
+        self.idle.frame = 0;
+        self.idle.buffer = 0;
+        self.heading = heading;
+
+        // heading points west/north for positive values
+        let Some((hx, hy)) = behavior::unit(heading.0 as f32, heading.1 as f32) else {
+            return Ok(());
+        };
+        let speed = self.speed;
+        self.walk(self.position.0 - hx * speed, self.position.1 - hy * speed)
+    }
+
+    /// Count clicks on the cat itself, made at clock time `at`. Too many in a short
+    /// time make it angry.
+    fn on_cat_click(&mut self, at: f64) {
+        let clicks = &mut self.angry.clicks;
+        clicks.push_back(at);
+        while let Some(time) = clicks.front() {
+            if at - time <= self.config.angry_window {
+                break;
+            }
+            clicks.pop_front();
+        }
+        if clicks.len() as u32 >= self.config.angry_clicks {
+            clicks.clear();
+            self.angry.cooldown = self.config.angry_cooldown;
+        }
+    }
+
+    /// Pick the cat up with the pointer at (x, y), pressed at clock time `at`
+    fn on_pointer_down(&mut self, x: i32, y: i32, at: f64) -> Result<(), ManzarError> {
+        if !self.config.draggable {
+            return Ok(());
+        }
+        self.wake_loop()?;
+        self.drag.active = true;
+        self.drag.velocity = (0.0, 0.0);
+        self.drag.samples.clear();
+        self.on_pointer_move(x, y, at)
+    }
+
+    fn on_pointer_move(&mut self, x: i32, y: i32, at: f64) -> Result<(), ManzarError> {
+        if !self.drag.active {
+            return Ok(());
+        }
+        self.drag.samples.push_back((at, x, y));
+        while let Some((time, _, _)) = self.drag.samples.front() {
+            if at - time <= THROW_WINDOW {
+                break;
+            }
+            self.drag.samples.pop_front();
+        }
+        self.move_to(x, y)
+    }
+
+    /// Let go of the cat, throwing it with the pointer's recent velocity
+    fn on_pointer_up(&mut self) {
+        if !self.drag.active {
+            return;
+        }
+        self.drag.active = false;
+
+        let samples = &self.drag.samples;
+        if let (Some(first), Some(last)) = (samples.front(), samples.back()) {
+            let elapsed = (last.0 - first.0) as f32;
+            if elapsed > 0.0 {
+                let per_tick = self.config.tick_interval as f32 / elapsed;
+                let vx = (last.1 - first.1) as f32 * per_tick;
+                let vy = (last.2 - first.2) as f32 * per_tick;
+                let speed = (vx.powi(2) + vy.powi(2)).sqrt();
+                let scale = if speed > MAX_THROW_SPEED {
+                    MAX_THROW_SPEED / speed
+                } else {
+                    1.0
+                };
+                self.drag.velocity = (vx * scale, vy * scale);
+            }
+        }
+        self.drag.samples.clear();
+    }
+
+    /// Coast along after a throw, slowing down with friction and bouncing off the viewport edges
+    fn fly(&mut self) -> Result<(), ManzarError> {
+        let (mut vx, mut vy) = self.drag.velocity;
+        let x = self.cat.0 + vx.round() as i32;
+        let y = self.cat.1 + vy.round() as i32;
+
+        let (clamped_x, clamped_y) =
+            self.bounds
+                .clamp(x, y, self.config.anchor(), self.config.size());
+        if clamped_x != x {
+            vx = -vx;
+        }
+        if clamped_y != y {
+            vy = -vy;
+        }
+
+        let friction = self.config.throw_friction;
+        self.drag.velocity = (vx * friction, vy * friction);
+        self.idle.frame = 0;
+        self.set_state(CatState::Alert)?;
+        self.set_sprite(&self.sprites.alert.clone())?;
+        self.move_to(clamped_x, clamped_y)
+    }
+
+    /// Accelerate towards the bottom of the bounds. Returns false once the cat
+    /// is resting on the ground.
+    fn fall(&mut self) -> Result<bool, ManzarError> {
+        let ground = self.bounds.bottom - (self.config.size() - self.config.anchor().1);
+        if self.cat.1 >= ground {
+            self.fall_speed = 0.0;
+            return Ok(false);
+        }
+
+        self.fall_speed += GRAVITY;
+        let y = (self.cat.1 + self.fall_speed.round() as i32).min(ground);
+        self.idle.frame = 0;
+        self.set_state(CatState::Falling)?;
+        self.set_sprite(&self.sprites.falling.clone())?;
+        self.move_to(self.cat.0, y)?;
+        Ok(true)
+    }
+
+    /// Work out the tile size from the `width` x `height` sheet `measure_sheet` loaded.
+    /// The configured `tile_size` stays if it didn't load or doesn't divide into the grid.
+    fn on_sheet_measured(&mut self, size: Option<(u32, u32)>) -> Result<(), ManzarError> {
+        self.measuring = false;
+        let (Some((width, height)), Some((columns, rows))) = (size, self.config.sheet_grid) else {
+            return Ok(());
+        };
+        let tile = width / columns;
+        if tile == 0 || width % columns != 0 || height % rows != 0 || height / rows != tile {
+            web_sys::console::warn_1(
+                &format!(
+                    "manzar: a {}x{} sheet doesn't split into {}x{} square tiles, keeping tile_size {}",
+                    width, height, columns, rows, self.config.tile_size
+                )
+                .into(),
+            );
+            return Ok(());
+        }
+        self.set_tile_size(tile as i32)
+    }
+
+    /// Re-measure the page after a scroll. A layout shift can leave the cat outside the
+    /// visible area, in which case it walks back in rather than jumping.
+    fn on_scroll(&mut self) -> Result<(), ManzarError> {
+        let window = web_sys::window().ok_or(ManzarError::NoWindow)?;
+        let visible = viewport_bounds(&window)?;
+
+        let (x, y) = visible.clamp(
+            self.cat.0,
+            self.cat.1,
+            self.config.anchor(),
+            self.config.size(),
+        );
+        if (x, y) != (self.cat.0, self.cat.1) {
+            self.mouse = Point(x, y);
+        }
+        Ok(())
+    }
+
+    /// Show the live stats in a fixed corner overlay, or tear it down once `debug` is off
+    #[cfg(feature = "debug")]
+    fn update_debug_hud(&mut self) -> Result<(), ManzarError> {
+        if !self.config.debug {
+            if let Some(hud) = self.debug_hud.take() {
+                hud.remove();
+            }
+            return Ok(());
+        }
+
+        let hud = match &self.debug_hud {
+            Some(hud) => hud.clone(),
+            None => {
+                let document = self
+                    .element
+                    .owner_document()
+                    .ok_or(ManzarError::NoDocument)?;
+                let body = document.body().ok_or(ManzarError::NoBody)?;
+                let hud = document
+                    .create_element("div")?
+                    .dyn_into::<HtmlElement>()
+                    .map_err(JsValue::from)?;
+                let styles = [
+                    ("position", "fixed"),
+                    ("bottom", "4px"),
+                    ("left", "4px"),
+                    ("padding", "2px 4px"),
+                    ("font", "11px monospace"),
+                    ("color", "#fff"),
+                    ("background", "rgba(0, 0, 0, 0.6)"),
+                    ("pointer-events", "none"),
+                    ("z-index", "2147483647"),
+                ];
+                for (prop, val) in styles.iter() {
+                    hud.style().set_property(prop, val)?;
+                }
+                body.append_child(&hud)?;
+                self.debug_hud = Some(hud.clone());
+                hud
+            }
+        };
+
+        hud.set_text_content(Some(&format!(
+            "state: {} | distance: {:.1} | tick: {} | position: ({}, {})",
+            self.state.name(),
+            behavior::distance(&self.cat, &self.mouse),
+            self.ticks,
+            self.cat.0,
+            self.cat.1,
+        )));
+        Ok(())
     }
 
     /// Change the sprite while respecting currently playing animations
-    fn set_sprite(&mut self, sprite: &Sprite) {
+    fn set_sprite(&mut self, sprite: &Sprite) -> Result<(), ManzarError> {
+        self.set_sprite_as(sprite, SpriteKind::Plain)
+    }
+
+    /// `set_sprite` for a sprite picked as `kind`
+    fn set_sprite_as(&mut self, sprite: &Sprite, kind: SpriteKind) -> Result<(), ManzarError> {
         let cur = &self.animation.sprite.clone();
-        let target = match cur {
+        let (target, kind) = match cur {
             Sprite::Animated(anim) => match &anim.duration {
-                AnimationDuration::Definite(_) => cur, // if we are currently playing a definite
-                // animation, lets finish it before changing sprites
-                AnimationDuration::Infinite => sprite,
+                // if we are currently playing a definite animation, lets finish it
+                // before changing sprites
+                AnimationDuration::Definite(_) => (cur, self.animation.kind),
+                AnimationDuration::Infinite => (sprite, kind),
             },
-            Sprite::Static(_) => sprite,
+            Sprite::Static(_) => (sprite, kind),
         };
-        self._set_sprite(target);
+        self._set_sprite_as(target, kind)
+    }
+
+    fn _set_sprite(&mut self, sprite: &Sprite) -> Result<(), ManzarError> {
+        self._set_sprite_as(sprite, SpriteKind::Plain)
     }
 
-    fn _set_sprite(&mut self, sprite: &Sprite) {
+    fn _set_sprite_as(&mut self, sprite: &Sprite, kind: SpriteKind) -> Result<(), ManzarError> {
+        // any sprite, resting ones included, starts over from its first frame. Carrying
+        // the count over would cut a definite animation short by the ticks played before it.
+        if *sprite != self.animation.sprite || kind != self.animation.kind {
+            self.animation.frame = 0;
+        }
         let pt = match sprite {
             Sprite::Animated(anim) => {
                 match anim.duration {
                     AnimationDuration::Definite(duration) => {
+                        if duration <= self.animation.frame && kind == SpriteKind::Yawn {
+                            // the yawn leads straight into sleep without restarting the idle cycle
+                            self.animation.frame = 0;
+                            let asleep = self
+                                .config
+                                .idle_stages
+                                .iter()
+                                .position(|stage| stage.asleep);
+                            let sleeping = match asleep {
+                                Some(index) => self.stage_sprite(index),
+                                None => self.sprites.idle.clone(),
+                            };
+                            return self._set_sprite(&sleeping);
+                        }
+                        if duration <= self.animation.frame && kind == SpriteKind::Fidget {
+                            // a fidget is part of being idle, so it doesn't restart the idle cycle
+                            self.animation.frame = 0;
+                            return self._set_sprite(&self.sprites.idle.clone());
+                        }
                         if duration <= self.animation.frame {
-                            self._set_sprite(&self.sprites.idle.clone());
+                            self._set_sprite(&self.sprites.idle.clone())?;
                             self.animation.frame = 0;
                             self.idle.frame = 0;
-                            self.frame = 0;
-                            return ();
+                            self.scratch_frame = 0;
+                            return Ok(());
                         }
                     }
                     AnimationDuration::Infinite => (),
                 }
-                let len = anim.states.len() as u32;
-                self.animation.frame = self.animation.frame + 1;
-                &anim.states[(((self.animation.frame / (100 / anim.speed)) as u32) % len) as usize]
+                let now = self.clock.now();
+                if self.animation.frame == 0 {
+                    self.animation.started = now;
+                }
+                self.animation.frame = self.animation.frame.saturating_add(1);
+                // frames are timed in `tick_interval`s of real time, so the animation keeps
+                // its pace however often this is called, even at `sleep_tick_interval`.
+                // Rounding to whole intervals absorbs the jitter of the timer.
+                let elapsed = (now - self.animation.started).max(0.0);
+                let intervals = (elapsed / self.config.tick_interval as f64).round() as u64;
+                anim.frame_at(intervals)
             }
             Sprite::Static(pt) => {
                 self.animation.frame = 0;
@@ -244,221 +1433,1737 @@ impl ManzarState {
             }
         };
         self.animation.sprite = sprite.clone();
-        self.element
-            .style()
-            .set_property(
-                "background-position",
-                &format!("{}px {}px", pt.0 * 32, pt.1 * 32),
-            )
-            .unwrap();
+        self.animation.kind = kind;
+        self.apply_sheet()?;
+        self.renderer.set_frame(pt)
     }
 
-    fn move_to(&mut self, x: i32, y: i32) {
-        let get_style = |v: i32| format!("{}px", v);
-        let style = self.element.style();
+    /// Show the current state's sheet, falling back to the shared one. Only
+    /// touches the renderer when the sheet actually changes.
+    fn apply_sheet(&mut self) -> Result<(), ManzarError> {
+        let url = self
+            .config
+            .state_sheets
+            .get(self.state.name())
+            .unwrap_or(&self.config.sheet_url);
+        if *url == self.sheet {
+            return Ok(());
+        }
+        self.sheet = url.clone();
+        self.renderer.set_sheet(url)
+    }
 
-        style
-            .set_property("left", get_style(x - 16).as_str())
-            .unwrap();
-        style
-            .set_property("top", get_style(y - 16).as_str())
-            .unwrap();
+    /// Move to a sub-pixel position. The page only sees whole pixels, but the
+    /// fraction is kept so slow steps add up instead of being rounded away.
+    fn glide_to(&mut self, x: f32, y: f32) -> Result<(), ManzarError> {
+        let rounding = self.config.position_rounding;
+        let (rounded_x, rounded_y) = (rounding.pixel(x), rounding.pixel(y));
+        self.move_to(rounded_x, rounded_y)?;
+        // an axis the bounds pulled the cat back on stays at the whole pixel
+        if self.cat.0 == rounded_x {
+            self.position.0 = x;
+        }
+        if self.cat.1 == rounded_y {
+            self.position.1 = y;
+        }
+        Ok(())
+    }
+
+    fn move_to(&mut self, x: i32, y: i32) -> Result<(), ManzarError> {
+        if self.config.trail {
+            self.trail.spawn(&self.element, self.clock.now())?;
+        }
+
+        let (anchor_x, anchor_y) = self.config.anchor();
+        let (x, y) = self
+            .bounds
+            .clamp(x, y, (anchor_x, anchor_y), self.config.size());
+
+        // only the drawn position snaps to the grid, the cat keeps its actual one
+        let rounding = self.config.position_rounding;
+        self.renderer
+            .set_position(rounding.snap(x - anchor_x), rounding.snap(y - anchor_y))?;
 
         self.cat = Point(x, y);
+        self.position = (x as f32, y as f32);
+        self.place_bubble()?;
+        if self.config.persist {
+            self.pending_save = Some(PERSIST_DELAY);
+        }
+        Ok(())
     }
 
-    fn get_compass_sprites(&self, direction: &str) -> Sprite {
-        let c = self.sprites.cardinal.clone();
-        let o = self.sprites.ordinal.clone();
-
-        match direction {
-            "N" => c.n,
-            "E" => c.e,
-            "W" => c.w,
-            "S" => c.s,
-            "NE" => o.ne,
-            "NW" => o.nw,
-            "SE" => o.se,
-            "SW" => o.sw,
-            _ => panic!("Invalid direction!"),
+    /// Keep the speech bubble centered above the cat
+    fn place_bubble(&mut self) -> Result<(), ManzarError> {
+        // a bubble removed by its timer is dropped here
+        if !self
+            .bubble
+            .as_ref()
+            .is_some_and(|bubble| bubble.is_connected())
+        {
+            self.bubble = None;
+            return Ok(());
         }
+        if let Some(bubble) = &self.bubble {
+            let (_, anchor_y) = self.config.anchor();
+            let style = bubble.style();
+            style.set_property("left", &format!("{}px", self.cat.0))?;
+            style.set_property("top", &format!("{}px", self.cat.1 - anchor_y))?;
+        }
+        Ok(())
     }
 }
 
-#[derive(Clone)]
-struct Manzar {
-    state: Rc<RefCell<ManzarState>>,
+/// The area of the element `selector` picks out, which a cat with `confine_to` moves in
+fn measure_container(document: &Document, selector: &str) -> Result<Bounds, ManzarError> {
+    let edges = document.query_selector(selector)?.map(|container| {
+        let rect = container.get_bounding_client_rect();
+        (rect.left(), rect.top(), rect.right(), rect.bottom())
+    });
+    container_bounds(selector, edges)
 }
 
-#[wasm_bindgen]
-pub unsafe fn start(sprites_path: String) -> Result<(), JsValue> {
-    let window = web_sys::window().expect("no window exists.");
-    let document = window.document().expect("no document exists.");
-    let body = document.body().expect("document does not have a body.");
-    let div = document
-        .create_element("div")
-        .unwrap()
-        .dyn_into::<HtmlElement>()?;
+/// Round the `(left, top, right, bottom)` edges of the container into bounds, if
+/// there is one
+fn container_bounds(
+    selector: &str,
+    edges: Option<(f64, f64, f64, f64)>,
+) -> Result<Bounds, ManzarError> {
+    let (left, top, right, bottom) =
+        edges.ok_or_else(|| ManzarError::NoContainer(selector.to_string()))?;
+    Ok(Bounds {
+        left: left.round() as i32,
+        top: top.round() as i32,
+        right: right.round() as i32,
+        bottom: bottom.round() as i32,
+    })
+}
+
+/// The visible part of the page, which is what the fixed position cat moves in
+fn viewport_bounds(window: &Window) -> Result<Bounds, ManzarError> {
+    Ok(Bounds {
+        left: 0,
+        top: 0,
+        right: window.inner_width()?.as_f64().unwrap_or_default() as i32,
+        bottom: window.inner_height()?.as_f64().unwrap_or_default() as i32,
+    })
+}
+
+/// Handle to a running cat
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct Manzar {
+    state: Rc<RefCell<ManzarState>>,
+}
+
+impl Manzar {
+    /// Run one tick, then call the heartbeat if one is due. The heartbeat is called
+    /// once the state is released, so it is free to use the handle.
+    fn advance(&self) -> Result<(), JsValue> {
+        let beat = self.run(|state| {
+            state.tick()?;
+            state.due_heartbeat()
+        })?;
+        if let Some((callback, detail)) = beat {
+            callback.call1(&JsValue::NULL, &detail)?;
+        }
+        Ok(())
+    }
+
+    /// Run `f` on the state, then dispatch the events it announced. That happens
+    /// once the state is released, so page listeners are free to use the handle.
+    fn run<T>(
+        &self,
+        f: impl FnOnce(&mut ManzarState) -> Result<T, ManzarError>,
+    ) -> Result<T, ManzarError> {
+        self.run_with(f, Manzar::dispatch)
+    }
+
+    /// `run`, handing the events to `deliver` instead of dispatching them
+    fn run_with<T>(
+        &self,
+        f: impl FnOnce(&mut ManzarState) -> Result<T, ManzarError>,
+        mut deliver: impl FnMut(&Manzar, CatEvent) -> Result<(), ManzarError>,
+    ) -> Result<T, ManzarError> {
+        let (result, events) = {
+            let mut state = self.state.borrow_mut();
+            let result = f(&mut state);
+            // the events up to a failure still happened
+            (result, std::mem::take(&mut state.events))
+        };
+        for event in events {
+            deliver(self, event)?;
+        }
+        result
+    }
+
+    /// Dispatch `event` on the cat's element as a bubbling `<event_prefix>:<name>` event.
+    /// Its `detail.id` is the element's id, which tells apart cats sharing the prefix.
+    fn dispatch(&self, event: CatEvent) -> Result<(), ManzarError> {
+        let (element, name) = {
+            let state = self.state.borrow();
+            let name = format!("{}:{}", state.config.event_prefix, event.name());
+            (state.element.clone(), name)
+        };
+        let detail = js_sys::Object::new();
+        js_sys::Reflect::set(&detail, &"id".into(), &element.id().into())?;
+        if let CatEvent::StateChange(state) = event {
+            js_sys::Reflect::set(&detail, &"state".into(), &state.name().into())?;
+        }
+        let init = js_sys::Object::new();
+        js_sys::Reflect::set(&init, &"detail".into(), &detail)?;
+        js_sys::Reflect::set(&init, &"bubbles".into(), &true.into())?;
+
+        let event = CustomEvent::new_with_event_init_dict(&name, init.unchecked_ref())?;
+        element.dispatch_event(&event)?;
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl Manzar {
+    /// Change how many pixels the cat moves per tick. The cat speeds up or slows
+    /// down over a few ticks, unless `instant_speed` is on. A speed below 0 is refused.
+    pub fn set_speed(&self, speed: i32) -> Result<(), ManzarError> {
+        if speed < 0 {
+            return Err(config::negative_speed(speed));
+        }
+        let mut state = self.state.borrow_mut();
+        state.target_speed = speed;
+        if state.config.instant_speed {
+            state.speed = speed as f32;
+        }
+        Ok(())
+    }
+
+    /// Stop the cat and take it off the page for good, after poofing out if `poof` is on.
+    /// Cats driven by `step` and stopped ones are taken off right away.
+    pub fn destroy(&self) -> Result<(), ManzarError> {
+        let mut state = self.state.borrow_mut();
+        if state.leaving.is_some() {
+            return Ok(());
+        }
+        let poof = state.sprites.poof_out.ticks();
+        match poof {
+            // still running, as the ticks play the poof
+            Some(ticks) if state.config.poof && state.interval.is_some() => {
+                state.animation.frame = 0;
+                state.leaving = Some(ticks);
+                Ok(())
+            }
+            _ => state.teardown(),
+        }
+    }
+
+    /// Make the cat come to (x, y) right away, whatever it is doing. It wakes up
+    /// and looks alert for a moment before heading over.
+    pub fn come_here(&self, x: i32, y: i32) -> Result<(), ManzarError> {
+        self.run(|state| {
+            state.mouse = Point(x, y);
+            state.homing = false;
+            state.intro.clear();
+            state.detour = None;
+            state.wake_loop()?;
+            // a fresh target, even if the pointer is outside the window
+            state.pointer_present = true;
+            state.idle.frame = 0;
+            state.idle.buffer = state.config.alert_ticks();
+            // drop whatever definite animation was playing, e.g. a scratch. The state
+            // goes first, as it picks the sheet the sprite is shown from.
+            state.animation.frame = 0;
+            state.set_state(CatState::Alert)?;
+            let alert = state.sprites.alert.clone();
+            state._set_sprite(&alert)
+        })
+    }
+
+    /// Chase `element` instead of the cursor. With several targets, the cat goes
+    /// for whichever is nearest on every tick.
+    pub fn add_target(&self, element: HtmlElement) {
+        self.state
+            .borrow_mut()
+            .targets
+            .push(Target::Element(element));
+    }
+
+    /// Like `add_target`, for a fixed point on the page
+    pub fn add_target_point(&self, x: i32, y: i32) {
+        self.state
+            .borrow_mut()
+            .targets
+            .push(Target::Point(Point(x, y)));
+    }
+
+    /// Forget every target and go back to chasing the cursor
+    pub fn clear_targets(&self) {
+        self.state.borrow_mut().targets.clear();
+    }
+
+    /// Show `text` in a speech bubble above the cat for `duration` ms,
+    /// replacing anything it was already saying
+    pub fn say(&self, text: &str, duration: u32) -> Result<(), ManzarError> {
+        let mut state = self.state.borrow_mut();
+        if let Some(old) = state.bubble.take() {
+            old.remove();
+        }
+
+        let document = web_sys::window()
+            .ok_or(ManzarError::NoWindow)?
+            .document()
+            .ok_or(ManzarError::NoDocument)?;
+        let bubble = document
+            .create_element("div")?
+            .dyn_into::<HtmlElement>()
+            .map_err(JsValue::from)?;
+        bubble.set_class_name("manzar-bubble");
+        bubble.set_text_content(Some(text));
+        let styles = [
+            ("position", "fixed"),
+            ("transform", "translate(-50%, -100%)"),
+            ("padding", "2px 6px"),
+            ("border-radius", "6px"),
+            ("background", "white"),
+            ("color", "black"),
+            ("font", "12px sans-serif"),
+            ("white-space", "nowrap"),
+            ("pointer-events", "none"),
+        ];
+        for (prop, val) in styles.iter() {
+            bubble.style().set_property(prop, val)?;
+        }
+        document
+            .body()
+            .ok_or(ManzarError::NoBody)?
+            .append_child(&bubble)?;
+        state.bubble = Some(bubble.clone());
+        state.place_bubble()?;
+
+        let cleanup = Closure::once_into_js(move || bubble.remove());
+        web_sys::window()
+            .ok_or(ManzarError::NoWindow)?
+            .set_timeout_with_callback_and_timeout_and_arguments_0(
+                cleanup.unchecked_ref(),
+                duration as i32,
+            )?;
+        Ok(())
+    }
+
+    /// Forget the stored position, so the next page load starts at `start_position`
+    pub fn clear_persistence(&self) -> Result<(), ManzarError> {
+        let mut state = self.state.borrow_mut();
+        state.pending_save = None;
+        persist::clear(&state.config.storage_key)
+    }
+
+    /// Call `callback` every `every` rendered ticks with `{ tick, x, y, state }`, to keep
+    /// other effects in step with the cat. Replaces any earlier heartbeat.
+    pub fn on_heartbeat(&self, callback: js_sys::Function, every: u32) -> Result<(), ManzarError> {
+        if every == 0 {
+            return Err(ManzarError::InvalidConfig(String::from(
+                "a heartbeat needs to run every 1 or more ticks",
+            )));
+        }
+        self.state.borrow_mut().heartbeat = Some(Heartbeat { callback, every });
+        Ok(())
+    }
+
+    /// Stop calling the heartbeat callback
+    pub fn clear_heartbeat(&self) {
+        self.state.borrow_mut().heartbeat = None;
+    }
+
+    /// The compass direction the walking cat faces, e.g. `"NE"`, or `"idle"` while it
+    /// isn't walking. This is the committed facing that picks the walking sprite, so
+    /// it doesn't flicker along with small wobbles of the path.
+    pub fn get_direction(&self) -> String {
+        let state = self.state.borrow();
+        let direction = behavior::compass(state.heading);
+        if state.state != CatState::Moving || direction.is_empty() {
+            return String::from("idle");
+        }
+        String::from(direction)
+    }
+
+    /// Turn the `debug` overlay on or off
+    #[cfg(feature = "debug")]
+    pub fn set_debug(&self, debug: bool) -> Result<(), ManzarError> {
+        let mut state = self.state.borrow_mut();
+        state.config.debug = debug;
+        // right away, as a paused cat wouldn't get to it
+        state.update_debug_hud()
+    }
+
+    /// Freeze the cat in place until `resume` is called
+    pub fn pause(&self) {
+        self.state.borrow_mut().paused = true;
+    }
+
+    pub fn resume(&self) {
+        self.state.borrow_mut().paused = false;
+    }
+
+    /// Advance a cat started with `manual_step` by exactly one tick. Cats running
+    /// the internal loop refuse, as the two would tick over each other. Animations
+    /// still go by the clock, so pair this with a `ManualClock` that is advanced by
+    /// `tick_interval` per step to replay the cat the same way every time.
+    pub fn step(&self) -> Result<(), JsValue> {
+        if !self.state.borrow().config.manual_step {
+            return Err(ManzarError::InvalidConfig(String::from(
+                "step() only drives cats started with manual_step, the others run their own loop",
+            ))
+            .into());
+        }
+        self.advance()
+    }
+
+    /// Stop the render loop for good. The cat stays on the page, frozen.
+    pub fn stop(&self) -> Result<(), ManzarError> {
+        self.state.borrow_mut().stop()
+    }
+
+    /// Take the cat off the page without losing its state. It stops moving
+    /// until `show` puts it back where it was.
+    pub fn hide(&self) -> Result<(), ManzarError> {
+        let mut state = self.state.borrow_mut();
+        state.visible = false;
+        state.renderer.set_visible(false)
+    }
+
+    /// Bring in a cat started with `start_hidden`, poofing in if `poof` is on.
+    /// Does nothing once the cat has been revealed, `show` brings back a hidden cat.
+    pub fn reveal(&self) -> Result<(), ManzarError> {
+        self.state.borrow_mut().reveal()
+    }
+
+    pub fn show(&self) -> Result<(), ManzarError> {
+        let mut state = self.state.borrow_mut();
+        state.visible = true;
+        state.awaiting_reveal = false;
+        state.renderer.set_visible(true)
+    }
+
+    /// Replace the whole sprite layout, and the sheet if the layout names one.
+    /// See `SpriteLayout` for the expected shape.
+    pub fn set_all_sprites(&self, sprites: JsValue) -> Result<(), ManzarError> {
+        let layout: SpriteLayout = serde_wasm_bindgen::from_value(sprites)?;
+        let mut state = self.state.borrow_mut();
+        let sheet_url = layout.sheet_url.clone();
+        let mut sprites = ManzarSprites::from(layout);
+        sprites.check()?;
+        // nothing is known about the layout of a new sheet
+        let grid = state.grid.filter(|_| sheet_url.is_none());
+        if let Some(grid) = grid {
+            sprites.check_grid(grid)?;
+        }
+        if let Some(url) = sheet_url {
+            // followers spawned from here on should use the new sheet too
+            state.config.sheet_url = url;
+        }
+        state.grid = grid;
+        // the sprites idle stages brought along aren't part of the layout
+        sprites.stages = std::mem::take(&mut state.sprites.stages);
+        state.sprites = sprites;
+
+        // frame indices into the old animations mean nothing for the new ones
+        let idle = state.sprites.idle.clone();
+        state.animation.frame = 0;
+        state._set_sprite(&idle)
+    }
+
+    /// Replace the sprite of a single state (see `ManzarSprites::get_mut` for the names)
+    /// without touching the rest of the sheet
+    pub fn set_state_sprite(&self, state: &str, sprite_config: JsValue) -> Result<(), ManzarError> {
+        let sprite: Sprite = serde_wasm_bindgen::from_value(sprite_config)?;
+        sprite.check(state)?;
+        let mut manzar = self.state.borrow_mut();
+        let manzar = &mut *manzar;
+        if let Some(frame) = manzar.grid.and_then(|grid| sprite.off_grid(grid)) {
+            return Err(ManzarError::InvalidConfig(format!(
+                "the {} sprite's frame [{}, {}] is outside the sheet",
+                state, frame.0, frame.1
+            )));
+        }
+        let slot = manzar
+            .sprites
+            .get_mut(state)
+            .ok_or_else(|| ManzarError::UnknownSpriteState(state.to_string()))?;
+
+        // restart the animation if the cat is currently showing the replaced sprite
+        if manzar.animation.sprite == *slot {
+            manzar.animation.sprite = sprite.clone();
+            manzar.animation.frame = 0;
+        }
+        *slot = sprite;
+        Ok(())
+    }
+}
+
+/// Spawn a cat, resolving to its handle. Called before the body has been parsed,
+/// e.g. from a script in `<head>`, the cat is spawned once `DOMContentLoaded` fires.
+/// A page that is done parsing without a body, e.g. a frameset, rejects with `NoBody`.
+#[wasm_bindgen]
+pub fn start(sprites_path: String, config: JsValue) -> Result<js_sys::Promise, JsValue> {
+    let builder = ManzarBuilder::from_config(parse_config(&config)?).sheet_url(sprites_path);
 
-    div.set_id("Manzar");
+    let document = web_sys::window()
+        .ok_or(ManzarError::NoWindow)?
+        .document()
+        .ok_or(ManzarError::NoDocument)?;
+    if document.body().is_some() {
+        return Ok(js_sys::Promise::resolve(&JsValue::from(builder.build()?)));
+    }
+    if !body_coming(&document.ready_state()) {
+        // DOMContentLoaded has been and gone
+        return Ok(js_sys::Promise::reject(&ManzarError::NoBody.into()));
+    }
+
+    // still parsing <head>, put the cat on the page once there is a body for it
+    let mut builder = Some(builder);
+    Ok(js_sys::Promise::new(&mut |resolve, reject| {
+        let Some(builder) = builder.take() else {
+            return;
+        };
+        let rejected = reject.clone();
+        let on_ready = Closure::once_into_js(move || {
+            let settled = match builder.build() {
+                Ok(manzar) => resolve.call1(&JsValue::NULL, &JsValue::from(manzar)),
+                Err(err) => rejected.call1(&JsValue::NULL, &JsValue::from(err)),
+            };
+            if let Err(err) = settled {
+                web_sys::console::error_1(&err);
+            }
+        });
+        if let Err(err) =
+            document.add_event_listener_with_callback("DOMContentLoaded", on_ready.unchecked_ref())
+        {
+            let _ = reject.call1(&JsValue::NULL, &err);
+        }
+    }))
+}
+
+/// Whether a page without a body is still going to get one, going by its
+/// `document.readyState`. It isn't once it is past parsing.
+fn body_coming(ready_state: &str) -> bool {
+    ready_state == "loading"
+}
+
+/// Load the sheet at `url` to work out the tile size from its dimensions and
+/// `sheet_grid`, see `ManzarState::on_sheet_measured`. The cat doesn't render until
+/// this is done.
+fn measure_sheet(url: &str, inputs: &Inputs) -> Result<(), ManzarError> {
+    let sheet = HtmlImageElement::new()?;
+
+    let loaded = sheet.clone();
+    let load_inputs = Rc::downgrade(inputs);
+    let onload = Closure::once_into_js(move || {
+        let size = (loaded.natural_width(), loaded.natural_height());
+        listeners::queue(&load_inputs, Input::SheetMeasured(Some(size)));
+    });
+    let error_inputs = Rc::downgrade(inputs);
+    let onerror = Closure::once_into_js(move || {
+        listeners::queue(&error_inputs, Input::SheetMeasured(None));
+    });
+
+    sheet.set_onload(Some(onload.unchecked_ref()));
+    sheet.set_onerror(Some(onerror.unchecked_ref()));
+    sheet.set_src(url);
+    Ok(())
+}
+
+/// The first id starting with `prefix` that isn't taken yet, which is `prefix` itself
+/// for the first cat
+fn unique_id(document: &Document, prefix: &str) -> String {
+    let mut id = String::from(prefix);
+    let mut n = 1;
+    while document.get_element_by_id(&id).is_some() {
+        n += 1;
+        id = format!("{}-{}", prefix, n);
+    }
+    id
+}
+
+/// The config for the options passed from JS, which may be left out altogether
+fn parse_config(config: &JsValue) -> Result<ManzarConfig, ManzarError> {
+    if config.is_undefined() || config.is_null() {
+        return Ok(ManzarConfig::default());
+    }
+    let mut parsed: ManzarConfig = serde_wasm_bindgen::from_value(config.clone())?;
+    if let Some(personality) = parsed.personality {
+        // options passed along with the personality win over its own
+        personality.apply(&mut parsed, |option| {
+            js_sys::Reflect::has(config, &JsValue::from_str(option)).unwrap_or(false)
+        });
+    }
+    Ok(parsed)
+}
+
+/// Spawn `n` cats at once, e.g. for a rain of cats, resolving to an array of their
+/// handles. They use the batch renderer whatever `config` says, and start out spread
+/// evenly across the viewport at the height of `start_position`. Unlike `start`, this
+/// fails with `NoBody` if there is no body yet. If one cat can't be spawned, the ones
+/// before it are taken off the page again.
+#[wasm_bindgen]
+pub fn spawn_many(
+    sprites_path: String,
+    n: u32,
+    config: JsValue,
+) -> Result<js_sys::Array, ManzarError> {
+    let mut config = parse_config(&config)?;
+    config.sheet_url = sprites_path;
+    config.renderer = RendererKind::Batch;
+    // they would all save to the same key
+    config.persist = false;
+    let window = web_sys::window().ok_or(ManzarError::NoWindow)?;
+    let width = viewport_bounds(&window)?.right as i64;
+
+    let mut cats: Vec<Manzar> = Vec::new();
+    for i in 0..n {
+        let mut cat = config.clone();
+        let x = width * (i as i64 + 1) / (n as i64 + 1);
+        cat.start_position = (x as i32, config.start_position.1);
+        // seeded cats would otherwise all do exactly the same
+        cat.seed = config.seed.map(|seed| seed.wrapping_add(i as u64));
+        match ManzarBuilder::from_config(cat).build() {
+            Ok(manzar) => cats.push(manzar),
+            Err(err) => {
+                for manzar in &cats {
+                    if let Err(cleanup) = manzar.state.borrow_mut().teardown() {
+                        web_sys::console::error_1(&cleanup.into());
+                    }
+                }
+                return Err(err);
+            }
+        }
+    }
+    Ok(cats.into_iter().map(JsValue::from).collect())
+}
+
+/// Start another cat that follows `leader` around, standing `offset_x`, `offset_y`
+/// pixels away from it. Apart from where it starts and the optional `FollowerOptions`,
+/// the follower uses a copy of the leader's config, which is its own from then on.
+#[wasm_bindgen]
+pub fn spawn_following(
+    leader: &Manzar,
+    offset_x: i32,
+    offset_y: i32,
+    options: JsValue,
+) -> Result<Manzar, ManzarError> {
+    let options: FollowerOptions = if options.is_undefined() || options.is_null() {
+        FollowerOptions::default()
+    } else {
+        serde_wasm_bindgen::from_value(options)?
+    };
+    let (mut config, cat, clock) = {
+        let leader = leader.state.borrow();
+        (
+            leader.config.clone(),
+            leader.cat.clone(),
+            leader.clock.clone(),
+        )
+    };
+    config.start_position = (cat.0 + offset_x, cat.1 + offset_y);
+    options.apply(&mut config)?;
 
+    let follower = start_manzar(config, clock, None)?;
+    follower.state.borrow_mut().follow = Some(Follow {
+        leader: Rc::downgrade(&leader.state),
+        offset: (offset_x, offset_y),
+        lag: options.lag,
+    });
+    Ok(follower)
+}
+
+/// Version of the manzar crate this bundle was built from, e.g. `"0.2.0"`
+#[wasm_bindgen]
+pub fn version() -> String {
+    String::from(env!("CARGO_PKG_VERSION"))
+}
+
+/// Comma separated Cargo features this bundle was built with, e.g. `"console_error_panic_hook,debug"`
+#[wasm_bindgen]
+pub fn features() -> String {
+    let features: &[(&str, bool)] = &[
+        (
+            "console_error_panic_hook",
+            cfg!(feature = "console_error_panic_hook"),
+        ),
+        ("debug", cfg!(feature = "debug")),
+    ];
+    features
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// The sprites the cat is drawn with, `sprites` or the default ones, with what
+/// `config` changes about them applied
+fn cat_sprites(
+    config: &ManzarConfig,
+    sprites: Option<ManzarSprites>,
+) -> Result<ManzarSprites, ManzarError> {
+    // custom sprites bring their own animation speeds
+    match sprites {
+        Some(sprites) => {
+            sprites.check()?;
+            sprites
+        }
+        None => ManzarSprites::default().with_speeds(&config.animation_speeds),
+    }
+    .with_layout(&config.sheet_layout)?
+    .with_scratch(&config.scratch_frames, config.scratch_duration)?
+    .with_stages(&config.idle_stages)?
+    .with_fidgets(&config.fidgets)
+}
+
+fn start_manzar(
+    config: ManzarConfig,
+    clock: Rc<dyn Clock>,
+    sprites: Option<ManzarSprites>,
+) -> Result<Manzar, ManzarError> {
+    #[cfg(feature = "console_error_panic_hook")]
+    console_error_panic_hook::set_once();
+
+    config.validate()?;
+
+    let window = web_sys::window().ok_or(ManzarError::NoWindow)?;
+    let document = window.document().ok_or(ManzarError::NoDocument)?;
+    let body = document.body().ok_or(ManzarError::NoBody)?;
+    let div = document
+        .create_element("div")?
+        .dyn_into::<HtmlElement>()
+        .map_err(JsValue::from)?;
+
+    div.set_id(&unique_id(&document, &config.id_prefix));
+
+    let size = format!("{}px", config.size());
+    let (anchor_x, anchor_y) = config.anchor();
+    let stored = if config.persist {
+        persist::load(&config.storage_key)
+    } else {
+        None
+    };
+    let (start_x, start_y) = match stored {
+        // the viewport may have shrunk since the position was saved
+        Some((x, y)) => viewport_bounds(&window)?.clamp(x, y, (anchor_x, anchor_y), config.size()),
+        None => config.start_position,
+    };
+    let (left, top) = (start_x - anchor_x, start_y - anchor_y);
     let styles: [(&str, &str); 7] = [
-        ("height", "32px"),
-        ("width", "32px"),
-        ("top", "16px"),
-        ("left", "16px"),
+        ("height", &size),
+        ("width", &size),
+        // the renderer moves the cat from the origin with a transform
+        ("top", "0"),
+        ("left", "0"),
         (
             "background-image",
-            &format!("url('{}')", sprites_path.as_str()),
+            &format!("url('{}')", config.sheet_url.as_str()),
         ),
         ("position", "fixed"),
-        ("imageRendering", "pixelated"),
+        ("image-rendering", "pixelated"),
     ];
 
     for (prop, val) in styles.iter() {
         div.style().set_property(prop, val)?;
     }
-    body.append_child(&div)?;
 
-    let cardinal = CardinalSprites {
-        n: Sprite::Animated(Animation {
-            states: vec![Point(-1, -2), Point(-1, -3)],
-            duration: AnimationDuration::Infinite,
-            speed: 100,
-        }),
-        e: Sprite::Animated(Animation {
-            states: vec![Point(-3, 0), Point(-3, -1)],
-            duration: AnimationDuration::Infinite,
-            speed: 100,
-        }),
-        s: Sprite::Animated(Animation {
-            states: vec![Point(-6, -3), Point(-7, -2)],
-            duration: AnimationDuration::Infinite,
-            speed: 100,
-        }),
-        w: Sprite::Animated(Animation {
-            states: vec![Point(-4, -2), Point(-4, -3)],
-            duration: AnimationDuration::Infinite,
-            speed: 100,
-        }),
+    let sprites = cat_sprites(&config, sprites)?;
+    // per-state sheets may be laid out differently, so only a single sheet is checked
+    let grid = config.sheet_grid.filter(|_| config.state_sheets.is_empty());
+    if let Some(grid) = grid {
+        sprites.check_grid(grid)?;
+    }
+    let mut renderer: Box<dyn Renderer> = match config.renderer {
+        RendererKind::Dom => Box::new(DomRenderer::new(
+            div.clone(),
+            &config.sheet_url,
+            config.tile_size,
+            config.size(),
+            config.fade_duration,
+        )?),
+        RendererKind::Canvas | RendererKind::Batch => {
+            // the div only stays around as the target for dispatched events
+            div.style().set_property("visibility", "hidden")?;
+            Box::new(CanvasRenderer::new(
+                &document,
+                config.renderer == RendererKind::Batch,
+                &config.sheet_url,
+                config.tile_size,
+                config.size(),
+                (left, top),
+            )?)
+        }
     };
-
-    let ordinal = OrdinalSprites {
-        ne: Sprite::Animated(Animation {
-            states: vec![Point(0, -2), Point(0, -3)],
-            duration: AnimationDuration::Infinite,
-            speed: 100,
-        }),
-        se: Sprite::Animated(Animation {
-            states: vec![Point(-5, -1), Point(-5, -2)],
-            duration: AnimationDuration::Infinite,
-            speed: 100,
-        }),
-        sw: Sprite::Animated(Animation {
-            states: vec![Point(-5, -3), Point(-6, -1)],
-            duration: AnimationDuration::Infinite,
-            speed: 100,
-        }),
-        nw: Sprite::Animated(Animation {
-            states: vec![Point(-1, 0), Point(-1, -1)],
-            duration: AnimationDuration::Infinite,
-            speed: 100,
-        }),
+    renderer.set_position(left, top)?;
+    let viewport = viewport_bounds(&window)?;
+    // a missing container fails the start instead of every tick after it
+    let bounds = match &config.confine_to {
+        Some(selector) => measure_container(&document, selector)?,
+        None => viewport,
     };
-
-    let scratch = ScratchSprites {
-        cat: Sprite::Animated(Animation {
-            states: vec![Point(-5, 0), Point(-6, 0), Point(-7, 0)],
-            duration: AnimationDuration::Definite(20),
-            speed: 100,
-        }),
-        cardinal: CardinalSprites {
-            n: Sprite::Animated(Animation {
-                states: vec![Point(0, 0), Point(0, -1)],
-                duration: AnimationDuration::Definite(20),
-                speed: 100,
-            }),
-            e: Sprite::Animated(Animation {
-                states: vec![Point(-2, -2), Point(-2, -3)],
-                duration: AnimationDuration::Definite(20),
-                speed: 100,
-            }),
-            w: Sprite::Animated(Animation {
-                states: vec![Point(-4, 0), Point(-4, -1)],
-                duration: AnimationDuration::Definite(20),
-                speed: 100,
-            }),
-            s: Sprite::Animated(Animation {
-                states: vec![Point(-7, -1), Point(-6, -2)],
-                duration: AnimationDuration::Definite(20),
-                speed: 100,
-            }),
-        },
+    body.append_child(&div)?;
+    let keyboard = config.keyboard;
+    let shown = if config.start_hidden {
+        renderer.set_visible(false)
+    } else if config.fade_duration > 0 {
+        renderer.set_visible(true)
+    } else {
+        Ok(())
     };
+    if let Err(err) = shown {
+        div.remove();
+        return Err(err);
+    }
 
-    let sprites = ManzarSprites {
-        idle: Sprite::Static(Point(-3, -3)),
-        alert: Sprite::Static(Point(-7, -3)),
-        tired: Sprite::Static(Point(-3, -2)),
-        sleeping: Sprite::Animated(Animation {
-            states: vec![Point(-2, 0), Point(-2, -1)],
-            duration: AnimationDuration::Infinite,
-            speed: 25,
-        }),
-        cardinal,
-        ordinal,
-        scratch,
+    let sheet_grid = config.sheet_grid;
+    let tick_interval = config.tick_interval;
+    let mut manzar_state = ManzarState::new(
+        div,
+        renderer,
+        config,
+        sprites,
+        (start_x, start_y),
+        bounds,
+        clock,
+    );
+    manzar_state.hidden = document.hidden();
+    manzar_state.grid = grid;
+    let inputs = manzar_state.inputs.clone();
+
+    let manzar = Manzar {
+        state: Rc::new(RefCell::new(manzar_state)),
     };
 
-    let idle = sprites.idle.clone();
+    RUNNING.with(|running| running.borrow_mut().push(Rc::clone(&manzar.state)));
 
-    let de = document.document_element().unwrap();
+    set_up(&manzar, || {
+        listeners::register(&inputs, keyboard)?;
+        if sheet_grid.is_some() {
+            measure_sheet(&manzar.state.borrow().config.sheet_url, &inputs)?;
+        }
+        let element = manzar.state.borrow().element.clone();
+        let clock = manzar.state.borrow().clock.clone();
+        let mut element_listeners = Vec::new();
+        if manzar.state.borrow().config.angry {
+            let click_inputs = Rc::downgrade(&inputs);
+            let click_clock = clock.clone();
+            element_listeners.push(Listener::new(element.clone().into(), "click", move |_| {
+                let at = click_clock.now();
+                listeners::queue(&click_inputs, Input::CatClick { at });
+            }));
+        }
 
-    let manzar_state = ManzarState {
-        element: div,
-        sprites,
-        mouse: Point(32, 32),
-        cat: Point(32, 32),
-        speed: 10,
-        frame: 0,
-        animation: AnimationState {
-            sprite: idle,
-            frame: 0,
-        },
-        idle: IdleState {
-            timeout: 50,
-            frame: 0,
-            buffer: 0,
-        },
-        window_size: (de.scroll_width(), de.scroll_height()),
+        if manzar.state.borrow().config.draggable {
+            let style = element.style();
+            style.set_property("cursor", "grab")?;
+            // stop touch screens from scrolling instead of dragging
+            style.set_property("touch-action", "none")?;
+
+            let down_inputs = Rc::downgrade(&inputs);
+            let down_clock = clock.clone();
+            let down_element = element.clone();
+            element_listeners.push(Listener::new(
+                element.clone().into(),
+                "pointerdown",
+                move |e| {
+                    let e = e.unchecked_into::<PointerEvent>();
+                    e.prevent_default();
+                    // keep receiving moves even if the pointer outruns the cat
+                    if let Err(err) = down_element.set_pointer_capture(e.pointer_id()) {
+                        web_sys::console::error_1(&err);
+                    }
+                    let (x, y, at) = (e.client_x(), e.client_y(), down_clock.now());
+                    listeners::queue(&down_inputs, Input::PointerDown { x, y, at });
+                },
+            ));
+            let move_inputs = Rc::downgrade(&inputs);
+            let move_clock = clock.clone();
+            element_listeners.push(Listener::new(
+                element.clone().into(),
+                "pointermove",
+                move |e| {
+                    let e = e.unchecked_into::<PointerEvent>();
+                    let (x, y, at) = (e.client_x(), e.client_y(), move_clock.now());
+                    listeners::queue(&move_inputs, Input::PointerMove { x, y, at });
+                },
+            ));
+            for event in ["pointerup", "pointercancel"] {
+                let up_inputs = Rc::downgrade(&inputs);
+                element_listeners.push(Listener::new(element.clone().into(), event, move |_| {
+                    listeners::queue(&up_inputs, Input::PointerUp);
+                }));
+            }
+        }
+
+        // kept with the cat before they are added, so teardown takes them all off
+        let mut state = manzar.state.borrow_mut();
+        state.element_listeners = element_listeners;
+        for listener in &state.element_listeners {
+            listener.attach()?;
+        }
+
+        if !state.config.manual_step {
+            // https://rustwasm.github.io/wasm-bindgen/examples/closures.html
+            let frame_state = Rc::downgrade(&manzar.state);
+            let frame_update = Closure::<dyn FnMut()>::new(move || {
+                // a torn down cat is gone, but its interval may have been due already
+                let Some(state) = frame_state.upgrade() else {
+                    return;
+                };
+                if let Err(err) = (Manzar { state }).advance() {
+                    web_sys::console::error_1(&err);
+                }
+            });
+            let interval = window
+                .set_interval_with_callback_and_timeout_and_arguments_0(
+                    frame_update.as_ref().unchecked_ref(),
+                    tick_interval as i32,
+                )
+                .map_err(ManzarError::NoLoop)?;
+            state.interval = Some(interval);
+            state.on_tick = Some(frame_update);
+        }
+        Ok(())
+    })?;
+
+    Ok(manzar)
+}
+
+/// Finish setting up a cat that is already on the page. A cat that can't be set up
+/// completely is taken off the page again, instead of being left standing there
+/// without a loop.
+fn set_up(
+    manzar: &Manzar,
+    setup: impl FnOnce() -> Result<(), ManzarError>,
+) -> Result<(), ManzarError> {
+    let Err(err) = setup() else {
+        return Ok(());
     };
+    if let Err(cleanup) = manzar.state.borrow_mut().teardown() {
+        web_sys::console::error_1(&cleanup.into());
+    }
+    Err(err)
+}
 
-    let manzar = Manzar {
-        state: Rc::new(RefCell::new(manzar_state)),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What the cat asked its renderer to do
+    #[derive(Clone, Debug, PartialEq)]
+    enum Drawn {
+        Frame(i32, i32),
+        Position(i32, i32),
+        Mirrored(bool),
+        Visible(bool),
+        Removed,
+        Sheet(String),
+        TileSize(i32, i32),
+    }
+
+    /// Writes down what it is asked to draw instead of drawing it
+    struct FakeRenderer(Rc<RefCell<Vec<Drawn>>>);
+
+    impl Renderer for FakeRenderer {
+        fn set_frame(&mut self, frame: &Point) -> Result<(), ManzarError> {
+            self.0.borrow_mut().push(Drawn::Frame(frame.0, frame.1));
+            Ok(())
+        }
+
+        fn set_position(&mut self, left: i32, top: i32) -> Result<(), ManzarError> {
+            self.0.borrow_mut().push(Drawn::Position(left, top));
+            Ok(())
+        }
+
+        fn set_mirrored(&mut self, mirrored: bool) -> Result<(), ManzarError> {
+            self.0.borrow_mut().push(Drawn::Mirrored(mirrored));
+            Ok(())
+        }
+
+        fn set_visible(&mut self, visible: bool) -> Result<(), ManzarError> {
+            self.0.borrow_mut().push(Drawn::Visible(visible));
+            Ok(())
+        }
+
+        fn remove(&mut self) -> Result<(), ManzarError> {
+            self.0.borrow_mut().push(Drawn::Removed);
+            Ok(())
+        }
+
+        fn set_sheet(&mut self, url: &str) -> Result<(), ManzarError> {
+            self.0.borrow_mut().push(Drawn::Sheet(String::from(url)));
+            Ok(())
+        }
+
+        fn set_tile_size(&mut self, tile_size: i32, size: i32) -> Result<(), ManzarError> {
+            self.0.borrow_mut().push(Drawn::TileSize(tile_size, size));
+            Ok(())
+        }
+    }
+
+    const VIEWPORT: Bounds = Bounds {
+        left: 0,
+        top: 0,
+        right: 800,
+        bottom: 600,
     };
 
-    // https://rustwasm.github.io/wasm-bindgen/examples/closures.html
+    /// A cat as `start_manzar` sets it up, on a page that only exists in its
+    /// renderer. The clock only moves when the test says so.
+    struct TestCat {
+        state: ManzarState,
+        drawn: Rc<RefCell<Vec<Drawn>>>,
+        clock: ManualClock,
+    }
 
-    let mouse_clone = manzar.clone();
+    impl TestCat {
+        fn new(config: ManzarConfig) -> Self {
+            Self::with_sprites(config, None)
+        }
 
-    let mouse_callback = Closure::<dyn FnMut(_)>::new(move |e: MouseEvent| {
-        mouse_clone.state.borrow_mut().on_mouse_down(e);
-    });
+        fn with_sprites(config: ManzarConfig, sprites: Option<ManzarSprites>) -> Self {
+            let config = ManzarConfig {
+                // the entropy comes from the page
+                seed: config.seed.or(Some(7)),
+                ..config
+            };
+            config.validate().unwrap();
+            let drawn = Rc::default();
+            let clock = ManualClock::new(0.0);
+            let sprites = cat_sprites(&config, sprites).unwrap();
+            let start = config.start_position;
+            let state = ManzarState::new(
+                // nothing is called on the element without a page
+                JsValue::UNDEFINED.unchecked_into(),
+                Box::new(FakeRenderer(Rc::clone(&drawn))),
+                config,
+                sprites,
+                start,
+                VIEWPORT,
+                Rc::new(clock.clone()),
+            );
+            Self {
+                state,
+                drawn,
+                clock,
+            }
+        }
 
-    let frame_clone = manzar.clone();
-    let frame_update = Closure::<dyn FnMut()>::new(move || {
-        frame_clone.state.borrow_mut().render();
-    });
+        /// Render one tick, `tick_interval` after the last one
+        fn render(&mut self) {
+            self.clock.advance(self.state.config.tick_interval as f64);
+            self.state.render().unwrap();
+        }
 
-    document
-        .add_event_listener_with_callback("mousedown", mouse_callback.as_ref().unchecked_ref())?;
-    window.set_interval_with_callback_and_timeout_and_arguments_0(
-        frame_update.as_ref().unchecked_ref(),
-        100,
-    )?;
+        /// Point the cat at (x, y) and render a tick
+        fn chase(&mut self, x: i32, y: i32) {
+            self.state.mouse = Point(x, y);
+            self.render();
+        }
 
-    mouse_callback.forget();
-    frame_update.forget();
+        /// Everything drawn so far, forgetting about it
+        fn take_drawn(&self) -> Vec<Drawn> {
+            std::mem::take(&mut self.drawn.borrow_mut())
+        }
 
-    Ok(())
+        fn at(&self) -> (i32, i32) {
+            (self.state.cat.0, self.state.cat.1)
+        }
+
+        /// The handle JS would get for this cat
+        fn into_handle(self) -> Manzar {
+            Manzar {
+                state: Rc::new(RefCell::new(self.state)),
+            }
+        }
+    }
+
+    #[test]
+    fn a_cat_walks_towards_the_pointer() {
+        let mut cat = TestCat::new(ManzarConfig::default());
+        cat.chase(232, 32);
+        assert!(cat.state.state == CatState::Moving);
+        assert_eq!(cat.at(), (42, 32));
+        // the top left corner, half a tile up and left of the cat
+        assert!(cat.take_drawn().contains(&Drawn::Position(26, 16)));
+        assert_eq!(behavior::compass(cat.state.heading), "E");
+    }
+
+    #[test]
+    fn a_cat_settles_down_once_it_gets_there() {
+        let mut cat = TestCat::new(ManzarConfig::default());
+        for _ in 0..30 {
+            cat.chase(132, 32);
+        }
+        assert_eq!(cat.at(), (132, 32));
+        assert!(cat.state.state == CatState::Idle);
+    }
+
+    #[test]
+    fn clicks_on_the_cat_during_a_tick_wait_for_the_next_one() {
+        let config = ManzarConfig {
+            angry: true,
+            angry_clicks: 2,
+            ..ManzarConfig::default()
+        };
+        let cat = TestCat::new(config);
+        let inputs = Rc::downgrade(&cat.state.inputs);
+        let manzar = cat.into_handle();
+        manzar
+            .run_with(
+                |state| {
+                    // what the click listener does, here while the tick holds the state
+                    listeners::queue(&inputs, Input::CatClick { at: 0.0 });
+                    listeners::queue(&inputs, Input::CatClick { at: 100.0 });
+                    state.render()
+                },
+                |_, _| Ok(()),
+            )
+            .unwrap();
+        assert!(manzar.state.borrow().state != CatState::Angry);
+
+        manzar
+            .run_with(
+                |state| {
+                    state.handle_inputs()?;
+                    state.render()
+                },
+                |_, _| Ok(()),
+            )
+            .unwrap();
+        assert!(manzar.state.borrow().state == CatState::Angry);
+    }
+
+    #[test]
+    fn state_changes_are_announced_once_and_listeners_can_use_the_handle() {
+        let manzar = TestCat::new(ManzarConfig::default()).into_handle();
+        let mut heard = Vec::new();
+        for _ in 0..3 {
+            manzar
+                .run_with(
+                    |state| {
+                        state.mouse = Point(400, 32);
+                        state.render()
+                    },
+                    |manzar, event| {
+                        // a page listener calling back into the cat
+                        manzar.set_speed(5)?;
+                        manzar.pause();
+                        manzar.resume();
+                        manzar.get_direction();
+                        heard.push(event);
+                        Ok(())
+                    },
+                )
+                .unwrap();
+        }
+        assert!(heard == [CatEvent::StateChange(CatState::Moving)]);
+        assert_eq!(manzar.state.borrow().target_speed, 5);
+    }
+
+    #[test]
+    fn sleep_and_wake_are_announced_once_per_nap() {
+        let cat = TestCat::new(ManzarConfig::default());
+        let clock = cat.clock.clone();
+        let tick = cat.state.config.tick_interval as f64;
+        let manzar = cat.into_handle();
+        let mut heard = Vec::new();
+        let tick_towards = |x: i32, heard: &mut Vec<CatEvent>| {
+            clock.advance(tick);
+            manzar
+                .run_with(
+                    |state| {
+                        state.mouse = Point(x, 32);
+                        state.render()
+                    },
+                    |manzar, event| {
+                        // e.g. a "zzz" indicator asking where the cat is
+                        manzar.get_direction();
+                        heard.push(event);
+                        Ok(())
+                    },
+                )
+                .unwrap();
+        };
+        for _ in 0..300 {
+            tick_towards(32, &mut heard);
+        }
+        for _ in 0..10 {
+            tick_towards(400, &mut heard);
+        }
+        let count = |wanted: CatEvent| heard.iter().filter(|&&event| event == wanted).count();
+        assert_eq!(count(CatEvent::Sleep), 1);
+        assert_eq!(count(CatEvent::Wake), 1);
+        assert_eq!(count(CatEvent::StateChange(CatState::Sleeping)), 1);
+        // woken up by the pointer, and on its way
+        let sleep = heard.iter().position(|&event| event == CatEvent::Sleep);
+        let wake = heard.iter().position(|&event| event == CatEvent::Wake);
+        assert!(sleep < wake);
+        assert!(heard.last() == Some(&CatEvent::StateChange(CatState::Moving)));
+    }
+
+    #[test]
+    fn poof_is_off_unless_asked_for() {
+        assert!(!ManzarConfig::default().poof);
+        // and the bundled sheet has nothing to poof with
+        let sprites = ManzarSprites::default();
+        assert!(sprites.poof_in.ticks().is_none() && sprites.poof_out.ticks().is_none());
+    }
+
+    #[test]
+    fn a_stepped_cat_is_removed_right_away() {
+        let sprites = ManzarSprites {
+            poof_out: Sprite::Animated(Animation {
+                duration: AnimationDuration::Definite(3),
+                ..Animation::new(vec![Point(-1, 0), Point(-2, 0), Point(-3, 0)])
+            }),
+            ..ManzarSprites::default()
+        };
+        let config = ManzarConfig {
+            poof: true,
+            manual_step: true,
+            ..ManzarConfig::default()
+        };
+        let cat = TestCat::with_sprites(config, Some(sprites));
+        let drawn = Rc::clone(&cat.drawn);
+        let manzar = cat.into_handle();
+        manzar.destroy().unwrap();
+        assert!(drawn.borrow().contains(&Drawn::Removed));
+        assert!(manzar.state.borrow().leaving.is_none());
+    }
+
+    #[test]
+    fn the_first_tick_on_the_pointer_shows_a_still_idle_cat() {
+        let mut cat = TestCat::new(ManzarConfig::default());
+        let idle = Drawn::Frame(-3, -3);
+        cat.render();
+        assert!(cat.state.state == CatState::Idle);
+        assert!(cat.take_drawn().contains(&idle));
+        for _ in 0..5 {
+            cat.render();
+        }
+        assert!(cat.state.state == CatState::Idle);
+        assert_eq!(cat.at(), (32, 32));
+        // nothing but the idle frame, and the facing it rests with
+        assert!(cat
+            .take_drawn()
+            .iter()
+            .all(|drawn| *drawn == idle || matches!(drawn, Drawn::Mirrored(false))));
+        // and the first move of the pointer gets it going
+        cat.chase(300, 32);
+        assert!(cat.state.state == CatState::Alert);
+    }
+
+    #[test]
+    fn a_cat_without_speed_stays_put() {
+        let config = ManzarConfig {
+            speed: 0,
+            ..ManzarConfig::default()
+        };
+        let mut cat = TestCat::new(config);
+        for _ in 0..10 {
+            cat.chase(300, 200);
+        }
+        assert_eq!(cat.at(), (32, 32));
+        assert!(cat.state.position.0.is_finite() && cat.state.position.1.is_finite());
+        assert!(cat.take_drawn().contains(&Drawn::Position(16, 16)));
+    }
+
+    #[test]
+    fn set_speed_refuses_negative_speeds() {
+        let manzar = TestCat::new(ManzarConfig::default()).into_handle();
+        assert!(matches!(
+            manzar.set_speed(-3),
+            Err(ManzarError::InvalidConfig(_))
+        ));
+        assert_eq!(manzar.state.borrow().target_speed, 10);
+        manzar.set_speed(0).unwrap();
+        assert_eq!(manzar.state.borrow().target_speed, 0);
+    }
+
+    #[test]
+    fn only_a_page_still_loading_is_waited_on_for_a_body() {
+        assert!(body_coming("loading"));
+        assert!(!body_coming("interactive"));
+        assert!(!body_coming("complete"));
+    }
+
+    #[test]
+    fn a_missing_container_is_an_error() {
+        assert!(matches!(
+            container_bounds("#desk", None),
+            Err(ManzarError::NoContainer(selector)) if selector == "#desk"
+        ));
+    }
+
+    #[test]
+    fn the_container_is_measured_to_the_nearest_pixel() {
+        let bounds = container_bounds("#desk", Some((10.4, 20.6, 310.5, 219.49))).unwrap();
+        assert_eq!(
+            (bounds.left, bounds.top, bounds.right, bounds.bottom),
+            (10, 21, 311, 219)
+        );
+    }
+
+    #[test]
+    fn a_confined_cat_scratches_at_the_container_edge_it_is_pressed_against() {
+        let mut cat = TestCat::new(ManzarConfig::default());
+        let (anchor_x, _) = cat.state.config.anchor();
+        let size = cat.state.config.size();
+        // well inside the window, which isn't where the cat stops
+        cat.state.bounds = Bounds {
+            left: 100,
+            top: 100,
+            right: 300,
+            bottom: 300,
+        };
+        cat.state.cat = Point(300 - (size - anchor_x), 200);
+        assert!(*cat.state.get_cardinal_scratch_sprite() == cat.state.sprites.scratch.cardinal.e);
+
+        cat.state.cat = Point(200, 200);
+        assert!(*cat.state.get_cardinal_scratch_sprite() == cat.state.sprites.scratch.cat);
+    }
+
+    #[test]
+    fn a_destroyed_cat_is_let_go_of() {
+        let manzar = TestCat::new(ManzarConfig::default()).into_handle();
+        let other = TestCat::new(ManzarConfig::default()).into_handle();
+        for cat in [&manzar, &other] {
+            RUNNING.with(|running| running.borrow_mut().push(Rc::clone(&cat.state)));
+        }
+        assert_eq!(Rc::strong_count(&manzar.state), 2);
+
+        manzar.destroy().unwrap();
+        // only the handle is left, and the other cat keeps running
+        assert_eq!(Rc::strong_count(&manzar.state), 1);
+        assert_eq!(Rc::strong_count(&other.state), 2);
+
+        other.destroy().unwrap();
+        assert_eq!(Rc::strong_count(&other.state), 1);
+        assert!(RUNNING.with(|running| running.borrow().is_empty()));
+    }
+
+    #[test]
+    fn a_fidget_with_the_yawns_frames_is_still_a_fidget() {
+        let short = Sprite::Animated(Animation {
+            duration: AnimationDuration::Definite(2),
+            ..Animation::new(vec![Point(-1, 0), Point(-2, 0)])
+        });
+        let sprites = ManzarSprites {
+            yawn: short.clone(),
+            fidget: short.clone(),
+            ..ManzarSprites::default()
+        };
+        let mut cat = TestCat::with_sprites(ManzarConfig::default(), Some(sprites));
+        let idle = cat.state.sprites.idle.clone();
+
+        for _ in 0..3 {
+            cat.state.set_sprite_as(&short, SpriteKind::Fidget).unwrap();
+        }
+        // back to idle, instead of dozing off as after a yawn
+        assert!(cat.state.animation.sprite == idle);
+        assert!(cat.state.animation.kind == SpriteKind::Plain);
+
+        for _ in 0..3 {
+            cat.state.set_sprite_as(&short, SpriteKind::Yawn).unwrap();
+        }
+        assert!(cat.state.animation.sprite == cat.state.sprites.sleeping);
+    }
+
+    #[test]
+    fn a_cat_that_cant_be_set_up_is_taken_off_the_page() {
+        let cat = TestCat::new(ManzarConfig::default());
+        let drawn = Rc::clone(&cat.drawn);
+        let manzar = cat.into_handle();
+        RUNNING.with(|running| running.borrow_mut().push(Rc::clone(&manzar.state)));
+
+        let setup = set_up(&manzar, || Err(ManzarError::NoBody));
+        let Err(err) = setup else {
+            panic!("the setup failed");
+        };
+        assert_eq!(err.to_string(), "document does not have a body");
+        assert!(drawn.borrow().contains(&Drawn::Removed));
+        assert_eq!(Rc::strong_count(&manzar.state), 1);
+    }
+
+    #[test]
+    fn a_cat_set_up_completely_stays() {
+        let cat = TestCat::new(ManzarConfig::default());
+        let drawn = Rc::clone(&cat.drawn);
+        let manzar = cat.into_handle();
+        assert!(set_up(&manzar, || Ok(())).is_ok());
+        assert!(!drawn.borrow().contains(&Drawn::Removed));
+    }
+
+    #[test]
+    fn a_cat_right_on_the_pointer_sits_still() {
+        let mut cat = TestCat::new(ManzarConfig::default());
+        let (x, y) = cat.at();
+        for _ in 0..5 {
+            cat.chase(x, y);
+        }
+        assert_eq!(cat.at(), (x, y));
+        assert!(cat.state.position.0.is_finite() && cat.state.position.1.is_finite());
+        assert!(cat.state.state == CatState::Idle);
+        // nothing to move, so no position is written, NaN or otherwise
+        assert!(!cat
+            .take_drawn()
+            .iter()
+            .any(|drawn| matches!(drawn, Drawn::Position(..))));
+    }
+
+    #[test]
+    fn the_sprite_is_drawn_around_its_anchor() {
+        let config = ManzarConfig {
+            anchor_x: Some(4),
+            anchor_y: Some(30),
+            ..ManzarConfig::default()
+        };
+        let mut cat = TestCat::new(config);
+        cat.chase(232, 32);
+        assert_eq!(cat.at(), (42, 32));
+        assert!(cat.take_drawn().contains(&Drawn::Position(42 - 4, 32 - 30)));
+    }
+
+    #[test]
+    fn a_cat_idle_for_ages_doesnt_overflow_its_counters() {
+        let mut cat = TestCat::new(ManzarConfig::default());
+        let (x, y) = cat.at();
+        cat.chase(x, y);
+        cat.state.idle.frame = u32::MAX - 1;
+        cat.state.scratch_frame = u32::MAX - 1;
+        for _ in 0..5 {
+            cat.chase(x, y);
+        }
+        let last_stage = cat.state.config.idle_stages.last().unwrap().after;
+        assert_eq!(cat.state.idle.frame, cat.state.idle.timeout + last_stage);
+        assert!(cat.state.scratch_frame < cat.state.config.scratch_frequency);
+        assert!(cat.state.state == CatState::Sleeping);
+    }
+
+    #[test]
+    fn a_cat_asleep_for_ages_doesnt_overflow_its_animation() {
+        let mut cat = TestCat::new(ManzarConfig::default());
+        let (x, y) = cat.at();
+        cat.chase(x, y);
+        cat.state.idle.frame = u32::MAX - 1;
+        cat.chase(x, y);
+        assert!(cat.state.state == CatState::Sleeping);
+        cat.state.animation.frame = u32::MAX - 1;
+        for _ in 0..5 {
+            cat.chase(x, y);
+        }
+        assert_eq!(cat.state.animation.frame, u32::MAX);
+        assert!(cat.state.state == CatState::Sleeping);
+    }
+
+    #[test]
+    fn a_wiggling_pointer_keeps_the_cat_awake() {
+        let wiggle = |deadzone_crossed: bool| {
+            let mut cat = TestCat::new(ManzarConfig::default());
+            let (x, y) = cat.at();
+            let last_stage = cat.state.config.idle_stages.last().unwrap().after;
+            let ticks = cat.state.idle.timeout + last_stage + 10;
+            let reach = if deadzone_crossed { 4 } else { 1 };
+            let mut states = Vec::new();
+            for tick in 0..ticks {
+                let dx = if tick % 2 == 0 { reach } else { -reach };
+                cat.state.on_mouse_move(Point(x + dx, y)).unwrap();
+                cat.chase(x + dx, y);
+                states.push(cat.state.state);
+            }
+            assert_eq!(cat.at(), (x, y));
+            states
+        };
+        assert!(!wiggle(true)
+            .iter()
+            .any(|state| matches!(state, CatState::Tired | CatState::Sleeping)));
+        // twitches inside the deadzone don't count as someone being around
+        assert!(wiggle(false).contains(&CatState::Sleeping));
+    }
+
+    /// Four frames, one after another along the sheet's top row
+    fn four_frames() -> Sprite {
+        Sprite::Animated(Animation::new(vec![
+            Point(0, 0),
+            Point(-1, 0),
+            Point(-2, 0),
+            Point(-3, 0),
+        ]))
+    }
+
+    #[test]
+    fn the_frame_shown_goes_by_the_time_passed() {
+        let mut cat = TestCat::new(ManzarConfig::default());
+        let sprite = four_frames();
+        let interval = cat.state.config.tick_interval as f64;
+        cat.state._set_sprite(&sprite).unwrap();
+        // however often it is redrawn in between
+        for _ in 0..5 {
+            cat.state._set_sprite(&sprite).unwrap();
+        }
+        cat.clock.advance(2.0 * interval);
+        cat.state._set_sprite(&sprite).unwrap();
+        // a late timer is rounded to the interval it belongs to
+        cat.clock.advance(0.6 * interval);
+        cat.state._set_sprite(&sprite).unwrap();
+        let frames: Vec<Drawn> = cat
+            .take_drawn()
+            .into_iter()
+            .filter(|drawn| matches!(drawn, Drawn::Frame(..)))
+            .collect();
+        assert!(frames[..6].iter().all(|frame| *frame == Drawn::Frame(0, 0)));
+        assert!(frames[6..] == [Drawn::Frame(-2, 0), Drawn::Frame(-3, 0)]);
+    }
+
+    #[test]
+    fn a_tab_coming_back_doesnt_make_the_cat_lurch() {
+        let mut cat = TestCat::new(ManzarConfig::default());
+        let interval = cat.state.config.tick_interval as f64;
+        let sprite = four_frames();
+        cat.state._set_sprite(&sprite).unwrap();
+        cat.clock.advance(interval);
+        assert!(cat.state.on_schedule());
+
+        // a minute in the background, and then the throttled ticks all at once
+        cat.clock.advance(60_000.0);
+        assert!(cat.state.on_schedule());
+        for _ in 0..10 {
+            assert!(!cat.state.on_schedule());
+        }
+        // the animation carries on as if the minute hadn't passed
+        cat.take_drawn();
+        cat.state._set_sprite(&sprite).unwrap();
+        assert!(cat.take_drawn().contains(&Drawn::Frame(-2, 0)));
+
+        // and the one tick that does run moves the cat a single step
+        let (x, y) = cat.at();
+        cat.state.mouse = Point(x + 500, y);
+        cat.state.render().unwrap();
+        let travelled = (cat.at().0 - x) as f32;
+        assert!(travelled > 0.0 && travelled <= cat.state.config.speed as f32);
+    }
+
+    #[test]
+    fn an_animated_tired_sprite_plays_its_frames() {
+        let sprites = ManzarSprites {
+            tired: four_frames(),
+            ..ManzarSprites::default()
+        };
+        let config = ManzarConfig {
+            scratch_enabled: false,
+            ..ManzarConfig::default()
+        };
+        let mut cat = TestCat::with_sprites(config, Some(sprites));
+        let (x, y) = cat.at();
+        while cat.state.state != CatState::Tired {
+            cat.chase(x, y);
+        }
+        cat.take_drawn();
+        for _ in 0..4 {
+            cat.chase(x, y);
+        }
+        assert!(cat.state.state == CatState::Tired);
+        let frames: Vec<Drawn> = cat
+            .take_drawn()
+            .into_iter()
+            .filter(|drawn| matches!(drawn, Drawn::Frame(..)))
+            .collect();
+        assert!(
+            frames
+                == [
+                    Drawn::Frame(-1, 0),
+                    Drawn::Frame(-2, 0),
+                    Drawn::Frame(-3, 0),
+                    Drawn::Frame(0, 0),
+                ]
+        );
+    }
+
+    #[test]
+    fn a_cat_doesnt_chase_a_pointer_out_of_range() {
+        let config = ManzarConfig {
+            max_chase_distance: Some(200.0),
+            ..ManzarConfig::default()
+        };
+        let mut cat = TestCat::new(config);
+        let (x, y) = cat.at();
+        for _ in 0..5 {
+            cat.chase(x + 300, y);
+        }
+        assert_eq!(cat.at(), (x, y));
+        assert!(cat.state.state != CatState::Moving);
+
+        // and goes after it again once it is back within range, after a startled moment
+        for _ in 0..=cat.state.config.alert_ticks() {
+            cat.chase(x + 150, y);
+        }
+        assert!(cat.at().0 > x);
+    }
+
+    #[test]
+    fn scratches_are_spaced_by_the_cooldown() {
+        let config = ManzarConfig {
+            start_position: (16, 300),
+            idle_timeout: 5,
+            scratch_frequency: 6,
+            scratch_cooldown: 30,
+            // awake all along, so nothing but the cooldown stops the scratching
+            idle_stages: vec![IdleStage {
+                after: 10_000,
+                sprite: String::from("sleeping"),
+                frames: None,
+                asleep: true,
+            }],
+            ..ManzarConfig::default()
+        };
+        let mut cat = TestCat::new(config);
+        let (x, y) = cat.at();
+        let mut scratched = Vec::new();
+        let mut wait = 0;
+        for tick in 0..400 {
+            cat.chase(x, y);
+            if cat.state.scratch_wait > wait {
+                scratched.push(tick);
+            }
+            wait = cat.state.scratch_wait;
+        }
+        let scratch = cat.state.sprites.scratch.cardinal.w.ticks().unwrap();
+        assert!(scratched.len() > 2, "{:?}", scratched);
+        for pair in scratched.windows(2) {
+            assert!(pair[1] - pair[0] >= scratch + 30, "{:?}", scratched);
+        }
+    }
+
+    #[test]
+    fn a_cat_covers_the_same_ground_straight_and_diagonally() {
+        let travelled = |dx: i32, dy: i32| {
+            let mut cat = TestCat::new(ManzarConfig {
+                start_position: (300, 300),
+                ..ManzarConfig::default()
+            });
+            let (x, y) = cat.at();
+            cat.chase(x + dx, y + dy);
+            let (after_x, after_y) = cat.state.position;
+            (after_x - x as f32).hypot(after_y - y as f32)
+        };
+        let straight = travelled(200, 0);
+        let speed = ManzarConfig::default().speed as f32;
+        assert!((straight - speed).abs() < 1e-3, "{}", straight);
+        for (dx, dy) in [(200, 200), (-150, 150), (120, -50)] {
+            let diagonal = travelled(dx, dy);
+            assert!(
+                (diagonal - straight).abs() < 1e-3,
+                "{} vs {}",
+                diagonal,
+                straight
+            );
+        }
+    }
+
+    #[test]
+    fn a_cat_in_a_collapsed_container_holds_still() {
+        let mut cat = TestCat::new(ManzarConfig::default());
+        let (x, y) = cat.at();
+        cat.state.bounds = Bounds {
+            left: 50,
+            top: 50,
+            right: 50,
+            bottom: 50,
+        };
+        for _ in 0..10 {
+            cat.chase(x + 200, y + 200);
+        }
+        assert_eq!(cat.at(), (x, y));
+        assert!(cat.take_drawn().is_empty());
+
+        // and goes on once the container has a size again
+        cat.state.bounds = VIEWPORT;
+        cat.chase(x + 200, y + 200);
+        assert!(!cat.take_drawn().is_empty());
+    }
+
+    #[test]
+    fn a_wider_scratch_margin_reaches_walls_farther_away() {
+        let scratch_at = |scratch_margin: u32| {
+            let mut cat = TestCat::new(ManzarConfig {
+                scratch_margin,
+                ..ManzarConfig::default()
+            });
+            let (anchor_x, _) = cat.state.config.anchor();
+            // 20px between the sprite and the west wall
+            cat.state.cat = Point(anchor_x + 20, 300);
+            let sprite = cat.state.get_cardinal_scratch_sprite().clone();
+            (sprite, cat.state.sprites.scratch.clone())
+        };
+        let (sprite, scratch) = scratch_at(10);
+        assert!(sprite == scratch.cat);
+        let (sprite, scratch) = scratch_at(25);
+        assert!(sprite == scratch.cardinal.w);
+    }
+
+    #[test]
+    fn without_the_alert_an_idle_cat_sets_off_right_away() {
+        let first_step = |alert_enabled: bool| {
+            let mut cat = TestCat::new(ManzarConfig {
+                alert_enabled,
+                ..ManzarConfig::default()
+            });
+            let (x, y) = cat.at();
+            for _ in 0..5 {
+                cat.chase(x, y);
+            }
+            cat.chase(x + 200, y);
+            cat.at() != (x, y)
+        };
+        assert!(first_step(false));
+        // where it would otherwise look up first
+        assert!(!first_step(true));
+    }
+
+    #[test]
+    fn followers_keep_up_at_their_own_speeds() {
+        let leader = Rc::new(RefCell::new(
+            TestCat::new(ManzarConfig {
+                start_position: (600, 300),
+                ..ManzarConfig::default()
+            })
+            .state,
+        ));
+        let follower = |speed: i32| {
+            let options = FollowerOptions {
+                speed: Some(speed),
+                ..FollowerOptions::default()
+            };
+            let mut config = ManzarConfig {
+                start_position: (100, 300),
+                ..leader.borrow().config.clone()
+            };
+            options.apply(&mut config).unwrap();
+            let mut cat = TestCat::new(config);
+            cat.state.follow = Some(Follow {
+                leader: Rc::downgrade(&leader),
+                offset: (-50, 0),
+                lag: options.lag,
+            });
+            cat
+        };
+        let mut cats = [follower(20), follower(5)];
+        let mut arrived = [None, None];
+        for tick in 0..200 {
+            for (cat, arrived) in cats.iter_mut().zip(&mut arrived) {
+                cat.state.follow_leader();
+                cat.render();
+                let close = behavior::distance(&cat.state.cat, &Point(550, 300)) <= 20.0;
+                if close && arrived.is_none() {
+                    *arrived = Some(tick);
+                }
+            }
+        }
+        let [Some(fast), Some(slow)] = arrived else {
+            panic!("both followers got there: {:?}", arrived);
+        };
+        assert!(fast * 3 < slow, "{} vs {}", fast, slow);
+    }
 }
@@ -0,0 +1,320 @@
+//! Page-wide listeners shared by every cat. They are added to the document and the
+//! window when the first cat starts, pass each event on to all the cats still around,
+//! and are taken off and dropped again once the last cat is torn down, so spawning and
+//! destroying cats doesn't pile up handlers.
+//!
+//! The listeners never touch a cat's state. They queue up what happened as `Input`s,
+//! which the cat works through at the start of its next tick, so an event arriving
+//! while the state is borrowed can't run into the borrow. The listeners on a cat's
+//! own element, and the sheet it measures, queue their inputs up the same way.
+
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    rc::{Rc, Weak},
+};
+
+use wasm_bindgen::{prelude::Closure, JsCast};
+use web_sys::{Event, EventTarget, KeyboardEvent, MouseEvent};
+
+use crate::{behavior::HeldKeys, error::ManzarError, sprites::Point};
+
+// Inputs kept for a cat that doesn't tick, e.g. a stopped one, before the oldest are dropped
+const MAX_INPUTS: usize = 64;
+
+/// Something that happened on the page, waiting for the cat's next tick
+#[derive(Clone)]
+pub(crate) enum Input {
+    MouseDown { x: i32, y: i32, button: i16 },
+    // the pointer entered (true) or left (false) the window
+    PointerPresent(bool),
+    // the tab went to the background (true) or came back
+    Hidden(bool),
+    Key { key: String, pressed: bool },
+    ContextMenu,
+    Scroll,
+    // pressing, moving and letting go of the pointer on the cat itself, at clock times
+    PointerDown { x: i32, y: i32, at: f64 },
+    PointerMove { x: i32, y: i32, at: f64 },
+    PointerUp,
+    // a click on the cat itself
+    CatClick { at: f64 },
+    // the width and height of the sheet measured for `sheet_grid`, None if it didn't load
+    SheetMeasured(Option<(u32, u32)>),
+}
+
+/// A cat's queue of inputs, oldest first
+pub(crate) type Inputs = Rc<RefCell<VecDeque<Input>>>;
+
+type Handler = Box<dyn FnMut(Event)>;
+
+/// A callback for `event` on `target`, kept around to take it off again
+pub(crate) struct Listener {
+    target: EventTarget,
+    event: &'static str,
+    callback: Closure<dyn FnMut(Event)>,
+}
+
+impl Listener {
+    pub(crate) fn new(
+        target: EventTarget,
+        event: &'static str,
+        callback: impl FnMut(Event) + 'static,
+    ) -> Self {
+        Self {
+            target,
+            event,
+            callback: Closure::new(callback),
+        }
+    }
+
+    pub(crate) fn attach(&self) -> Result<(), ManzarError> {
+        Ok(self
+            .target
+            .add_event_listener_with_callback(self.event, self.callback.as_ref().unchecked_ref())?)
+    }
+
+    pub(crate) fn detach(&self) -> Result<(), ManzarError> {
+        Ok(self.target.remove_event_listener_with_callback(
+            self.event,
+            self.callback.as_ref().unchecked_ref(),
+        )?)
+    }
+}
+
+struct Cat {
+    inputs: Weak<RefCell<VecDeque<Input>>>,
+    // steered with the keyboard, so movement keys mustn't scroll the page
+    keyboard: bool,
+}
+
+#[derive(Default)]
+struct Registry {
+    cats: Vec<Cat>,
+    // on the page while there are cats, none of which these can tear down
+    listeners: Vec<Listener>,
+    // the pointer can move many times per tick, so moves are only counted here and
+    // each cat picks up the latest position once per tick
+    pointer_moves: u64,
+    pointer: Option<Point>,
+}
+
+impl Registry {
+    /// Add a cat, telling whether it is the only one and needs the listeners added
+    fn add(&mut self, inputs: &Inputs, keyboard: bool) -> bool {
+        // a dropped cat takes its queue with it, and goes too
+        self.cats.retain(|cat| cat.inputs.strong_count() > 0);
+        self.cats.push(Cat {
+            inputs: Rc::downgrade(inputs),
+            keyboard,
+        });
+        self.cats.len() == 1
+    }
+
+    /// Remove a cat, telling whether it was the last one and the listeners can go
+    fn remove(&mut self, inputs: &Inputs) -> bool {
+        let had_cats = !self.cats.is_empty();
+        self.cats.retain(|cat| {
+            cat.inputs
+                .upgrade()
+                .is_some_and(|other| !Rc::ptr_eq(&other, inputs))
+        });
+        had_cats && self.cats.is_empty()
+    }
+
+    /// Note down where the pointer moved to, for the cats to pick up on their next tick
+    fn record_pointer(&mut self, pointer: Point) {
+        self.pointer_moves += 1;
+        self.pointer = Some(pointer);
+    }
+}
+
+thread_local! {
+    static REGISTRY: RefCell<Registry> = RefCell::new(Registry::default());
+}
+
+/// Queue `input` up for a single cat, unless it is gone
+pub(crate) fn queue(inputs: &Weak<RefCell<VecDeque<Input>>>, input: Input) {
+    if let Some(inputs) = inputs.upgrade() {
+        let mut inputs = inputs.borrow_mut();
+        if inputs.len() >= MAX_INPUTS {
+            inputs.pop_front();
+        }
+        inputs.push_back(input);
+    }
+}
+
+/// Queue `input` up for every cat
+fn dispatch(input: Input) {
+    REGISTRY.with(|registry| {
+        for cat in &registry.borrow().cats {
+            queue(&cat.inputs, input.clone());
+        }
+    })
+}
+
+fn on_key(event: Event, pressed: bool) {
+    let event = event.unchecked_into::<KeyboardEvent>();
+    let key = event.key();
+    let steering = REGISTRY.with(|registry| registry.borrow().cats.iter().any(|cat| cat.keyboard));
+    if steering && HeldKeys::moves(&key) {
+        // keep arrow keys from scrolling the page
+        event.prevent_default();
+    }
+    dispatch(Input::Key { key, pressed });
+}
+
+fn listeners() -> Result<Vec<Listener>, ManzarError> {
+    let window = web_sys::window().ok_or(ManzarError::NoWindow)?;
+    let document = window.document().ok_or(ManzarError::NoDocument)?;
+    let visibility_document = document.clone();
+
+    let on_document: [(&'static str, Handler); 8] = [
+        (
+            "mousedown",
+            Box::new(|e| {
+                let e = e.unchecked_ref::<MouseEvent>();
+                dispatch(Input::MouseDown {
+                    x: e.client_x(),
+                    y: e.client_y(),
+                    button: e.button(),
+                })
+            }),
+        ),
+        (
+            "mousemove",
+            Box::new(|e| {
+                let e = e.unchecked_ref::<MouseEvent>();
+                let pointer = Point(e.client_x(), e.client_y());
+                REGISTRY.with(|registry| registry.borrow_mut().record_pointer(pointer))
+            }),
+        ),
+        (
+            "visibilitychange",
+            Box::new(move |_| dispatch(Input::Hidden(visibility_document.hidden()))),
+        ),
+        // mouseout/mouseover without a related target mean the pointer left or entered the window
+        (
+            "mouseout",
+            Box::new(|e| {
+                if e.unchecked_ref::<MouseEvent>().related_target().is_none() {
+                    dispatch(Input::PointerPresent(false))
+                }
+            }),
+        ),
+        (
+            "mouseover",
+            Box::new(|_| dispatch(Input::PointerPresent(true))),
+        ),
+        ("keydown", Box::new(|e| on_key(e, true))),
+        ("keyup", Box::new(|e| on_key(e, false))),
+        ("contextmenu", Box::new(|_| dispatch(Input::ContextMenu))),
+    ];
+
+    let mut listeners: Vec<_> = on_document
+        .into_iter()
+        .map(|(event, handle)| Listener::new(document.clone().into(), event, handle))
+        .collect();
+    listeners.push(Listener::new(window.into(), "scroll", |_| {
+        dispatch(Input::Scroll)
+    }));
+    Ok(listeners)
+}
+
+/// How often the pointer has moved so far, to compare against later
+pub(crate) fn pointer_moves() -> u64 {
+    REGISTRY.with(|registry| registry.borrow().pointer_moves)
+}
+
+/// Where the pointer is now, if it has moved since the count `seen`, along with
+/// the new count
+pub(crate) fn pointer_since(seen: u64) -> Option<(u64, Point)> {
+    REGISTRY.with(|registry| {
+        let registry = registry.borrow();
+        let pointer = registry.pointer.clone()?;
+        (registry.pointer_moves != seen).then_some((registry.pointer_moves, pointer))
+    })
+}
+
+/// Start queueing page events up in `inputs`, adding the listeners if they belong
+/// to the only cat
+pub(crate) fn register(inputs: &Inputs, keyboard: bool) -> Result<(), ManzarError> {
+    REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        if registry.add(inputs, keyboard) {
+            // kept before they are added, so a failure halfway is taken off again
+            registry.listeners = listeners()?;
+            for listener in &registry.listeners {
+                listener.attach()?;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Stop queueing page events up in `inputs`, taking the listeners off and dropping
+/// them if they belonged to the last cat
+pub(crate) fn unregister(inputs: &Inputs) -> Result<(), ManzarError> {
+    REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        if registry.remove(inputs) {
+            for listener in std::mem::take(&mut registry.listeners) {
+                listener.detach()?;
+            }
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_listeners_are_added_for_the_first_cat_and_go_with_the_last() {
+        let mut registry = Registry::default();
+        let cats: Vec<Inputs> = (0..3).map(|_| Inputs::default()).collect();
+        assert!(registry.add(&cats[0], false));
+        assert!(!registry.add(&cats[1], false));
+        assert!(!registry.add(&cats[2], true));
+
+        assert!(!registry.remove(&cats[1]));
+        assert!(!registry.remove(&cats[0]));
+        assert!(registry.remove(&cats[2]));
+        // nothing left to take off twice
+        assert!(!registry.remove(&cats[2]));
+
+        // and back on for the next cat to come along
+        assert!(registry.add(&cats[1], false));
+        assert!(registry.remove(&cats[1]));
+    }
+
+    #[test]
+    fn a_dropped_cat_doesnt_keep_the_listeners_around() {
+        let mut registry = Registry::default();
+        let kept = Inputs::default();
+        let dropped = Inputs::default();
+        registry.add(&kept, false);
+        registry.add(&dropped, false);
+        drop(dropped);
+        assert!(registry.remove(&kept));
+    }
+
+    #[test]
+    fn only_the_latest_of_many_pointer_moves_is_picked_up() {
+        let seen = pointer_moves();
+        REGISTRY.with(|registry| {
+            let mut registry = registry.borrow_mut();
+            for x in 0..100 {
+                registry.record_pointer(Point(x, 2 * x));
+            }
+        });
+        let Some((moves, pointer)) = pointer_since(seen) else {
+            panic!("the pointer moved");
+        };
+        assert_eq!(moves, seen + 100);
+        assert!(pointer == Point(99, 198));
+        // and nothing new until it moves again
+        assert!(pointer_since(moves).is_none());
+    }
+}
@@ -0,0 +1,32 @@
+//! Remembering where the cat was across page loads, through `localStorage`.
+//! Storage can be missing or blocked (e.g. private browsing), which simply
+//! means nothing is remembered.
+
+use web_sys::Storage;
+
+use crate::error::ManzarError;
+
+fn storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+/// The position stored under `key`, stored as `"x,y"`
+pub(crate) fn load(key: &str) -> Option<(i32, i32)> {
+    let value = storage()?.get_item(key).ok()??;
+    let (x, y) = value.split_once(',')?;
+    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+pub(crate) fn save(key: &str, x: i32, y: i32) -> Result<(), ManzarError> {
+    if let Some(storage) = storage() {
+        storage.set_item(key, &format!("{},{}", x, y))?;
+    }
+    Ok(())
+}
+
+pub(crate) fn clear(key: &str) -> Result<(), ManzarError> {
+    if let Some(storage) = storage() {
+        storage.remove_item(key)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,776 @@
+//! Backends that put the cat on the page. `ManzarState` decides what frame to show
+//! and where, a `Renderer` takes care of actually drawing it.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+use serde::Deserialize;
+use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
+use web_sys::{
+    CanvasRenderingContext2d, Document, HtmlCanvasElement, HtmlElement, HtmlImageElement,
+};
+
+use crate::{error::ManzarError, listeners::Listener, sprites::Point};
+
+// Id of the canvas shared by every cat using the canvas backend
+const CANVAS_ID: &str = "ManzarCanvas";
+// Id of the canvas the batch backend draws all of its cats onto at once
+const BATCH_CANVAS_ID: &str = "ManzarBatch";
+
+#[derive(Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RendererKind {
+    /// Move a div around and show frames through its `background-position`
+    #[default]
+    Dom,
+    /// Draw frames onto a page-covering canvas shared by all cats
+    Canvas,
+    /// Collect what changed and redraw all cats together once per animation frame,
+    /// for pages with a lot of them
+    Batch,
+}
+
+pub(crate) trait Renderer {
+    /// Show the frame at `frame`, given in tile offsets on the sheet
+    fn set_frame(&mut self, frame: &Point) -> Result<(), ManzarError>;
+    /// Place the top left corner of the sprite, in viewport pixels
+    fn set_position(&mut self, left: i32, top: i32) -> Result<(), ManzarError>;
+    /// Flip the sprite horizontally
+    fn set_mirrored(&mut self, mirrored: bool) -> Result<(), ManzarError>;
+    /// Take the sprite off the page, or put it back where it was
+    fn set_visible(&mut self, visible: bool) -> Result<(), ManzarError>;
+    /// Take the sprite off the page for good
+    fn remove(&mut self) -> Result<(), ManzarError>;
+    /// Switch to the sprite sheet at `url`
+    fn set_sheet(&mut self, url: &str) -> Result<(), ManzarError>;
+    /// Use frames of `tile_size` pixels on the sheet, drawn `size` pixels big
+    fn set_tile_size(&mut self, tile_size: i32, size: i32) -> Result<(), ManzarError>;
+}
+
+pub(crate) struct DomRenderer {
+    element: HtmlElement,
+    sheet_url: String,
+    tile_size: i32,
+    // on-screen size of a frame, which is the tile size when the cat isn't scaled
+    size: i32,
+    // top left corner, applied as a translation from the viewport's origin
+    position: (i32, i32),
+    mirrored: bool,
+    styles: StyleWrites,
+    // opacity transition when showing/hiding, in ms
+    fade: u32,
+    // bumped on every show/hide so a pending fade-out can tell it was superseded
+    fade_generation: Rc<Cell<u32>>,
+}
+
+/// Sets a property of the element's style
+type SetStyle = Box<dyn FnMut(&str, &str) -> Result<(), JsValue>>;
+
+/// The styles the dom renderer writes every tick, skipping the ones that wouldn't
+/// change. A page that rejects these (e.g. a strict CSP) would do so on every tick,
+/// so after the first failure the cat simply stays as it is, instead of erroring
+/// each tick.
+struct StyleWrites {
+    set: SetStyle,
+    // last values written, so unchanged ones aren't written again
+    frame: Option<Point>,
+    transform: String,
+    // the page rejected a write, so they aren't attempted anymore
+    blocked: bool,
+}
+
+impl StyleWrites {
+    fn new(set: impl FnMut(&str, &str) -> Result<(), JsValue> + 'static) -> Self {
+        Self {
+            set: Box::new(set),
+            frame: None,
+            transform: String::new(),
+            blocked: false,
+        }
+    }
+
+    /// Write `value` to `property`. Returns the error if this is the write the page
+    /// rejected, after which there are no more.
+    fn write(&mut self, property: &'static str, value: &str) -> Option<Rejected> {
+        if self.blocked {
+            return None;
+        }
+        let err = (self.set)(property, value).err()?;
+        self.blocked = true;
+        Some(Rejected { property, err })
+    }
+
+    /// Show `frame`, on a sheet drawn in tiles of `size` pixels
+    fn frame(&mut self, frame: &Point, size: i32) -> Option<Rejected> {
+        if self.frame.as_ref() == Some(frame) {
+            return None;
+        }
+        self.frame = Some(frame.clone());
+        let position = format!("{}px {}px", frame.0 * size, frame.1 * size);
+        self.write("background-position", &position)
+    }
+
+    /// Write the `transform`. Every transform the cat needs is composed here so that
+    /// none of them overwrite each other. The translation comes first, so the
+    /// mirroring still happens around the sprite's own center.
+    fn transform(&mut self, (left, top): (i32, i32), mirrored: bool) -> Option<Rejected> {
+        let mut transform = format!("translate({}px, {}px)", left, top);
+        if mirrored {
+            transform.push_str(" scaleX(-1)");
+        }
+        if transform == self.transform {
+            return None;
+        }
+        let rejected = self.write("transform", &transform);
+        self.transform = transform;
+        rejected
+    }
+}
+
+/// A style write the page rejected
+struct Rejected {
+    property: &'static str,
+    err: JsValue,
+}
+
+impl Rejected {
+    /// Let the page's author know why the cat stopped being redrawn
+    fn warn(self) {
+        web_sys::console::warn_1(
+            &format!(
+                "manzar: the page rejected setting {}, the cat won't be redrawn: {}",
+                self.property,
+                ManzarError::from(self.err)
+            )
+            .into(),
+        );
+    }
+}
+
+impl DomRenderer {
+    pub(crate) fn new(
+        element: HtmlElement,
+        sheet_url: &str,
+        tile_size: i32,
+        size: i32,
+        fade: u32,
+    ) -> Result<Self, ManzarError> {
+        if fade > 0 {
+            let style = element.style();
+            style.set_property("opacity", "0")?;
+            style.set_property("transition", &format!("opacity {}ms", fade))?;
+        }
+        if size != tile_size {
+            scale_background(&element, sheet_url, tile_size, size)?;
+        }
+        let style = element.style();
+        Ok(Self {
+            styles: StyleWrites::new(move |property, value| style.set_property(property, value)),
+            element,
+            sheet_url: String::from(sheet_url),
+            tile_size,
+            size,
+            position: (0, 0),
+            mirrored: false,
+            fade,
+            fade_generation: Rc::new(Cell::new(0)),
+        })
+    }
+
+    /// Start fading the element out, calling `done` once it is invisible
+    fn fade_out(&self, done: impl FnOnce() + 'static) -> Result<(), ManzarError> {
+        self.element.style().set_property("opacity", "0")?;
+        let done = Closure::once_into_js(done);
+        web_sys::window()
+            .ok_or(ManzarError::NoWindow)?
+            .set_timeout_with_callback_and_timeout_and_arguments_0(
+                done.unchecked_ref(),
+                self.fade as i32,
+            )?;
+        Ok(())
+    }
+
+    /// Write the element's `transform`, logging it if the page turned it down
+    fn apply_transform(&mut self) -> Result<(), ManzarError> {
+        if let Some(rejected) = self.styles.transform(self.position, self.mirrored) {
+            rejected.warn();
+        }
+        Ok(())
+    }
+}
+
+impl Renderer for DomRenderer {
+    fn set_frame(&mut self, frame: &Point) -> Result<(), ManzarError> {
+        if let Some(rejected) = self.styles.frame(frame, self.size) {
+            rejected.warn();
+        }
+        Ok(())
+    }
+
+    fn set_position(&mut self, left: i32, top: i32) -> Result<(), ManzarError> {
+        if self.position == (left, top) && !self.styles.transform.is_empty() {
+            return Ok(());
+        }
+        // a transform only needs compositing, where left/top would redo layout every tick
+        self.position = (left, top);
+        self.apply_transform()
+    }
+
+    fn set_mirrored(&mut self, mirrored: bool) -> Result<(), ManzarError> {
+        if self.mirrored == mirrored {
+            return Ok(());
+        }
+        self.mirrored = mirrored;
+        self.apply_transform()
+    }
+
+    fn set_visible(&mut self, visible: bool) -> Result<(), ManzarError> {
+        let style = self.element.style();
+        if self.fade == 0 {
+            let display = if visible { "" } else { "none" };
+            style.set_property("display", display)?;
+            return Ok(());
+        }
+
+        let generation = self.fade_generation.get().wrapping_add(1);
+        self.fade_generation.set(generation);
+        if visible {
+            style.set_property("display", "")?;
+            // flush the pending display change, otherwise there is nothing to transition from
+            self.element.offset_width();
+            style.set_property("opacity", "1")?;
+            return Ok(());
+        }
+
+        // only take the element off the page once the fade has finished,
+        // and only if it wasn't shown again in the meantime
+        let element = self.element.clone();
+        let current = self.fade_generation.clone();
+        self.fade_out(move || {
+            if current.get() == generation {
+                let _ = element.style().set_property("display", "none");
+            }
+        })
+    }
+
+    fn remove(&mut self) -> Result<(), ManzarError> {
+        if self.fade == 0 {
+            self.element.remove();
+            return Ok(());
+        }
+        let element = self.element.clone();
+        self.fade_out(move || element.remove())
+    }
+
+    fn set_sheet(&mut self, url: &str) -> Result<(), ManzarError> {
+        self.sheet_url = String::from(url);
+        self.element
+            .style()
+            .set_property("background-image", &format!("url('{}')", url))?;
+        if self.size != self.tile_size {
+            scale_background(&self.element, url, self.tile_size, self.size)?;
+        }
+        Ok(())
+    }
+
+    fn set_tile_size(&mut self, tile_size: i32, size: i32) -> Result<(), ManzarError> {
+        self.tile_size = tile_size;
+        self.size = size;
+        // the same frame now sits at a different offset
+        self.styles.frame = None;
+        let style = self.element.style();
+        style.set_property("width", &format!("{}px", size))?;
+        style.set_property("height", &format!("{}px", size))?;
+        if size == tile_size {
+            style.remove_property("background-size")?;
+        } else {
+            scale_background(&self.element, &self.sheet_url, tile_size, size)?;
+        }
+        Ok(())
+    }
+}
+
+/// Where and how one cat on a shared canvas is drawn
+struct Slot {
+    // url of the sheet, loaded into the canvas's `sheets`
+    sheet: String,
+    tile_size: i32,
+    size: i32,
+    frame: Point,
+    position: (i32, i32),
+    mirrored: bool,
+    visible: bool,
+}
+
+/// A canvas covering the viewport and every cat drawn onto it. The cats only update
+/// their slot, and the whole canvas is redrawn from the slots, so cats overlapping
+/// each other don't wipe each other out.
+struct SharedCanvas {
+    canvas: HtmlCanvasElement,
+    context: CanvasRenderingContext2d,
+    // sheets by url, loaded once however many cats are drawn off them
+    sheets: HashMap<String, HtmlImageElement>,
+    slots: Vec<Rc<RefCell<Slot>>>,
+    // keeps the canvas the size of the window
+    on_resize: Listener,
+    // runs `draw` on the next animation frame, for the batch renderer which doesn't
+    // redraw on every change
+    on_frame: Option<Closure<dyn FnMut()>>,
+    // id of the animation frame a redraw is waiting for
+    pending: Option<i32>,
+}
+
+thread_local! {
+    // by id, created along with the first cat drawn onto it and removed with the last
+    static CANVASES: RefCell<HashMap<&'static str, SharedCanvas>> = RefCell::default();
+}
+
+/// Draws the cat onto a canvas shared by every cat using the same backend. With
+/// the canvas backend every change redraws the canvas right away. The batch backend
+/// collects the changes and redraws once on the next animation frame, however many
+/// of its cats changed.
+pub(crate) struct CanvasRenderer {
+    // CANVAS_ID or BATCH_CANVAS_ID
+    id: &'static str,
+    slot: Rc<RefCell<Slot>>,
+}
+
+impl CanvasRenderer {
+    pub(crate) fn new(
+        document: &Document,
+        batched: bool,
+        sheet_url: &str,
+        tile_size: i32,
+        size: i32,
+        position: (i32, i32),
+    ) -> Result<Self, ManzarError> {
+        let id = if batched { BATCH_CANVAS_ID } else { CANVAS_ID };
+        open_canvas(document, id, batched)?;
+        let slot = Rc::new(RefCell::new(Slot {
+            sheet: String::from(sheet_url),
+            tile_size,
+            size,
+            frame: Point(0, 0),
+            position,
+            mirrored: false,
+            visible: true,
+        }));
+        CANVASES.with(|canvases| {
+            if let Some(shared) = canvases.borrow_mut().get_mut(id) {
+                shared.slots.push(slot.clone());
+            }
+        });
+        let mut renderer = Self { id, slot };
+        if let Err(err) = load_sheet(id, sheet_url).and_then(|()| redraw(id)) {
+            // taking the canvas off again if it was only there for this cat
+            renderer.remove()?;
+            return Err(err);
+        }
+        Ok(renderer)
+    }
+
+    /// Apply `change` to the slot, redrawing the canvas if it changed anything
+    fn update(&mut self, change: impl FnOnce(&mut Slot) -> bool) -> Result<(), ManzarError> {
+        if change(&mut self.slot.borrow_mut()) {
+            redraw(self.id)?;
+        }
+        Ok(())
+    }
+}
+
+impl Renderer for CanvasRenderer {
+    fn set_frame(&mut self, frame: &Point) -> Result<(), ManzarError> {
+        self.update(|slot| std::mem::replace(&mut slot.frame, frame.clone()) != *frame)
+    }
+
+    fn set_position(&mut self, left: i32, top: i32) -> Result<(), ManzarError> {
+        self.update(|slot| std::mem::replace(&mut slot.position, (left, top)) != (left, top))
+    }
+
+    fn set_mirrored(&mut self, mirrored: bool) -> Result<(), ManzarError> {
+        self.update(|slot| std::mem::replace(&mut slot.mirrored, mirrored) != mirrored)
+    }
+
+    fn set_visible(&mut self, visible: bool) -> Result<(), ManzarError> {
+        self.update(|slot| std::mem::replace(&mut slot.visible, visible) != visible)
+    }
+
+    fn remove(&mut self) -> Result<(), ManzarError> {
+        let last = CANVASES.with(|canvases| {
+            let mut canvases = canvases.borrow_mut();
+            let shared = canvases.get_mut(self.id)?;
+            shared.slots.retain(|slot| !Rc::ptr_eq(slot, &self.slot));
+            if !shared.slots.is_empty() {
+                return None;
+            }
+            canvases.remove(self.id)
+        });
+        match last {
+            Some(shared) => close_canvas(shared),
+            None => redraw(self.id),
+        }
+    }
+
+    fn set_sheet(&mut self, url: &str) -> Result<(), ManzarError> {
+        // blank until the new sheet has loaded, its onload redraws the cat
+        load_sheet(self.id, url)?;
+        self.update(|slot| {
+            slot.sheet = String::from(url);
+            true
+        })
+    }
+
+    fn set_tile_size(&mut self, tile_size: i32, size: i32) -> Result<(), ManzarError> {
+        self.update(|slot| {
+            slot.tile_size = tile_size;
+            slot.size = size;
+            true
+        })
+    }
+}
+
+/// Set up the canvas with `id` for drawing, unless there already is a cat on it
+fn open_canvas(document: &Document, id: &'static str, batched: bool) -> Result<(), ManzarError> {
+    if CANVASES.with(|canvases| canvases.borrow().contains_key(id)) {
+        return Ok(());
+    }
+    let window = web_sys::window().ok_or(ManzarError::NoWindow)?;
+    let canvas = shared_canvas(document, id)?;
+    let context = match context_2d(&canvas) {
+        Ok(context) => context,
+        Err(err) => {
+            canvas.remove();
+            return Err(err);
+        }
+    };
+    let on_resize = Listener::new(window.into(), "resize", move |_| {
+        if let Err(err) = resize(id) {
+            web_sys::console::error_1(&err.into());
+        }
+    });
+    if let Err(err) = on_resize.attach() {
+        canvas.remove();
+        return Err(err);
+    }
+    let on_frame = batched.then(|| {
+        Closure::new(move || {
+            CANVASES.with(|canvases| {
+                if let Some(shared) = canvases.borrow_mut().get_mut(id) {
+                    shared.pending = None;
+                }
+            });
+            draw(id);
+        })
+    });
+    let shared = SharedCanvas {
+        canvas,
+        context,
+        sheets: HashMap::new(),
+        slots: Vec::new(),
+        on_resize,
+        on_frame,
+        pending: None,
+    };
+    CANVASES.with(|canvases| canvases.borrow_mut().insert(id, shared));
+    Ok(())
+}
+
+/// Take a canvas the last cat has left off the page, along with its listeners
+fn close_canvas(shared: SharedCanvas) -> Result<(), ManzarError> {
+    let window = web_sys::window().ok_or(ManzarError::NoWindow)?;
+    if let Some(pending) = shared.pending {
+        // its callback is dropped along with the canvas
+        window.cancel_animation_frame(pending)?;
+    }
+    shared.on_resize.detach()?;
+    shared.canvas.remove();
+    Ok(())
+}
+
+/// The sheet at `url` on the canvas with `id`, loading it if no cat has used it
+/// yet. Cats drawn off a sheet that is still loading show up once it has.
+fn load_sheet(id: &'static str, url: &str) -> Result<(), ManzarError> {
+    CANVASES.with(|canvases| {
+        let mut canvases = canvases.borrow_mut();
+        let Some(shared) = canvases.get_mut(id) else {
+            return Ok(());
+        };
+        if shared.sheets.contains_key(url) {
+            return Ok(());
+        }
+        let sheet = HtmlImageElement::new()?;
+        let onload = Closure::once_into_js(move || {
+            if let Err(err) = redraw(id) {
+                web_sys::console::error_1(&err.into());
+            }
+        });
+        sheet.set_onload(Some(onload.unchecked_ref()));
+        sheet.set_src(url);
+        shared.sheets.insert(String::from(url), sheet);
+        Ok(())
+    })
+}
+
+/// Redraw the canvas with `id`, right away or on the next animation frame for the
+/// batch renderer, unless that is already due
+fn redraw(id: &'static str) -> Result<(), ManzarError> {
+    let drawn_now = CANVASES.with(|canvases| -> Result<bool, ManzarError> {
+        let mut canvases = canvases.borrow_mut();
+        let Some(shared) = canvases.get_mut(id) else {
+            return Ok(false);
+        };
+        let Some(on_frame) = &shared.on_frame else {
+            return Ok(true);
+        };
+        if shared.pending.is_none() {
+            let pending = web_sys::window()
+                .ok_or(ManzarError::NoWindow)?
+                .request_animation_frame(on_frame.as_ref().unchecked_ref())
+                .map_err(ManzarError::NoLoop)?;
+            shared.pending = Some(pending);
+        }
+        Ok(false)
+    })?;
+    if drawn_now {
+        draw(id);
+    }
+    Ok(())
+}
+
+/// Cover the window again after it was resized, which also clears the canvas
+fn resize(id: &'static str) -> Result<(), ManzarError> {
+    let window = web_sys::window().ok_or(ManzarError::NoWindow)?;
+    let width = window.inner_width()?.as_f64().unwrap_or_default() as u32;
+    let height = window.inner_height()?.as_f64().unwrap_or_default() as u32;
+    CANVASES.with(|canvases| {
+        if let Some(shared) = canvases.borrow().get(id) {
+            shared.canvas.set_width(width);
+            shared.canvas.set_height(height);
+            // resetting the size resets the context too
+            shared.context.set_image_smoothing_enabled(false);
+        }
+    });
+    draw(id);
+    Ok(())
+}
+
+/// Clear the canvas with `id` and draw all of its visible cats onto it
+fn draw(id: &'static str) {
+    CANVASES.with(|canvases| {
+        let canvases = canvases.borrow();
+        let Some(shared) = canvases.get(id) else {
+            return;
+        };
+        let drawn = (|| -> Result<(), ManzarError> {
+            let (width, height) = (shared.canvas.width(), shared.canvas.height());
+            shared
+                .context
+                .clear_rect(0.0, 0.0, width as f64, height as f64);
+            for slot in &shared.slots {
+                let slot = slot.borrow();
+                let Some(sheet) = shared.sheets.get(&slot.sheet) else {
+                    continue;
+                };
+                // the sheet's onload redraws the canvas once it's there
+                if !slot.visible || !sheet.complete() {
+                    continue;
+                }
+                draw_frame(
+                    &shared.context,
+                    sheet,
+                    slot.tile_size,
+                    slot.size,
+                    &slot.frame,
+                    slot.position,
+                    slot.mirrored,
+                )?;
+            }
+            Ok(())
+        })();
+        if let Err(err) = drawn {
+            web_sys::console::error_1(&err.into());
+        }
+    })
+}
+
+/// Draw the frame at `frame` on `sheet` with its top left corner at `position`
+fn draw_frame(
+    context: &CanvasRenderingContext2d,
+    sheet: &HtmlImageElement,
+    tile_size: i32,
+    size: i32,
+    frame: &Point,
+    position: (i32, i32),
+    mirrored: bool,
+) -> Result<(), ManzarError> {
+    let tile = tile_size as f64;
+    let size = size as f64;
+    let (left, top) = position;
+    context.save();
+    context.translate(left as f64, top as f64)?;
+    if mirrored {
+        context.translate(size, 0.0)?;
+        context.scale(-1.0, 1.0)?;
+    }
+    context.draw_image_with_html_image_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+        sheet,
+        (-frame.0 * tile_size) as f64,
+        (-frame.1 * tile_size) as f64,
+        tile,
+        tile,
+        0.0,
+        0.0,
+        size,
+        size,
+    )?;
+    context.restore();
+    Ok(())
+}
+
+/// Stretch the element's background so frames are `size` instead of `tile_size`
+/// pixels. This needs the dimensions of the sheet, so it happens once it has loaded.
+fn scale_background(
+    element: &HtmlElement,
+    sheet_url: &str,
+    tile_size: i32,
+    size: i32,
+) -> Result<(), ManzarError> {
+    let sheet = HtmlImageElement::new()?;
+    let element = element.clone();
+    let loaded = sheet.clone();
+    let onload = Closure::once_into_js(move || {
+        // scaled as integers so every frame lands exactly on a multiple of `size`
+        let width = loaded.natural_width() as i32 / tile_size * size;
+        let height = loaded.natural_height() as i32 / tile_size * size;
+        let _ = element
+            .style()
+            .set_property("background-size", &format!("{}px {}px", width, height));
+    });
+    sheet.set_onload(Some(onload.unchecked_ref()));
+    sheet.set_src(sheet_url);
+    Ok(())
+}
+
+/// Find the canvas with `id` covering the viewport, creating it for the first cat
+fn shared_canvas(document: &Document, id: &str) -> Result<HtmlCanvasElement, ManzarError> {
+    if let Some(canvas) = document.get_element_by_id(id) {
+        return canvas
+            .dyn_into::<HtmlCanvasElement>()
+            .map_err(|_| ManzarError::Dom(format!("#{} is not a canvas", id).into()));
+    }
+
+    let window = web_sys::window().ok_or(ManzarError::NoWindow)?;
+    let body = document.body().ok_or(ManzarError::NoBody)?;
+    let canvas = document
+        .create_element("canvas")?
+        .dyn_into::<HtmlCanvasElement>()
+        .map_err(wasm_bindgen::JsValue::from)?;
+    canvas.set_id(id);
+    canvas.set_width(window.inner_width()?.as_f64().unwrap_or_default() as u32);
+    canvas.set_height(window.inner_height()?.as_f64().unwrap_or_default() as u32);
+
+    let styles = [
+        ("position", "fixed"),
+        ("top", "0"),
+        ("left", "0"),
+        ("pointer-events", "none"),
+    ];
+    for (prop, val) in styles.iter() {
+        canvas.style().set_property(prop, val)?;
+    }
+    body.append_child(&canvas)?;
+    Ok(canvas)
+}
+
+/// The 2d context of `canvas`, set up for pixel art
+fn context_2d(canvas: &HtmlCanvasElement) -> Result<CanvasRenderingContext2d, ManzarError> {
+    let context = canvas
+        .get_context("2d")?
+        .ok_or_else(|| ManzarError::Dom("canvas has no 2d context".into()))?
+        .dyn_into::<CanvasRenderingContext2d>()
+        .map_err(wasm_bindgen::JsValue::from)?;
+    context.set_image_smoothing_enabled(false);
+    Ok(context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ManzarConfig;
+
+    type Written = Rc<RefCell<Vec<(String, String)>>>;
+
+    /// Style writes that are written down instead of reaching a page
+    fn recorded() -> (StyleWrites, Written) {
+        let written = Written::default();
+        let log = Rc::clone(&written);
+        let styles = StyleWrites::new(move |property, value| {
+            log.borrow_mut()
+                .push((String::from(property), String::from(value)));
+            Ok(())
+        });
+        (styles, written)
+    }
+
+    #[test]
+    fn a_cat_holding_still_writes_nothing_more() {
+        let (mut styles, written) = recorded();
+        for _ in 0..3 {
+            assert!(styles.frame(&Point(-1, 0), 32).is_none());
+            assert!(styles.transform((10, 20), false).is_none());
+        }
+        assert_eq!(
+            *written.borrow(),
+            [
+                (
+                    String::from("background-position"),
+                    String::from("-32px 0px")
+                ),
+                (
+                    String::from("transform"),
+                    String::from("translate(10px, 20px)")
+                ),
+            ]
+        );
+
+        // and only what changed once it moves
+        styles.transform((11, 20), false);
+        styles.frame(&Point(-1, 0), 32);
+        assert_eq!(written.borrow().len(), 3);
+        assert_eq!(written.borrow()[2].1, "translate(11px, 20px)");
+    }
+
+    #[test]
+    fn a_rejected_write_is_reported_once_and_then_not_tried_again() {
+        let attempts = Rc::new(Cell::new(0));
+        let tried = Rc::clone(&attempts);
+        let mut styles = StyleWrites::new(move |_, _| {
+            tried.set(tried.get() + 1);
+            Err(JsValue::NULL)
+        });
+        let rejected = styles.frame(&Point(-1, 0), 32);
+        assert!(rejected.is_some_and(|rejected| rejected.property == "background-position"));
+        for x in 0..5 {
+            assert!(styles.transform((x, 0), false).is_none());
+            assert!(styles.frame(&Point(-x, -1), 32).is_none());
+        }
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn a_cat_scaled_up_twice_shows_the_same_frame_twice_as_big() {
+        let config = ManzarConfig {
+            scale: 2.0,
+            ..ManzarConfig::default()
+        };
+        assert_eq!(config.size(), 2 * config.tile_size);
+        assert_eq!(config.anchor(), (config.tile_size, config.tile_size));
+
+        let (mut styles, written) = recorded();
+        styles.frame(&Point(-1, -2), config.size());
+        assert_eq!(written.borrow()[0].1, "-64px -128px");
+    }
+}
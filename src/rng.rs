@@ -0,0 +1,54 @@
+//! Randomness for the cat's whims. A small xorshift generator rather than
+//! `Math.random` so that a fixed `seed` replays the exact same behavior.
+
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        // xorshift never leaves the all-zero state, so that one seed is nudged
+        Self(seed.max(1))
+    }
+
+    /// A generator seeded from `Math.random`, for when no seed was configured
+    pub(crate) fn from_entropy() -> Self {
+        Self::new((js_sys::Math::random() * u64::MAX as f64) as u64)
+    }
+
+    // xorshift64*
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.0 = x;
+        x.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Uniform in [0, 1)
+    pub(crate) fn next_f32(&mut self) -> f32 {
+        // the top 24 bits are exactly representable in an f32
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// True with probability `p`
+    pub(crate) fn chance(&mut self, p: f32) -> bool {
+        self.next_f32() < p
+    }
+
+    /// Index of one of the `weights`, each picked with a chance proportional to its
+    /// weight. The weights have to be 0 or more, with at least one above 0.
+    pub(crate) fn pick_weighted(&mut self, weights: &[f32]) -> usize {
+        let mut roll = self.next_f32() * weights.iter().sum::<f32>();
+        for (index, &weight) in weights.iter().enumerate() {
+            if roll < weight {
+                return index;
+            }
+            roll -= weight;
+        }
+        // rounding can leave a sliver past the last weight
+        weights
+            .iter()
+            .rposition(|&weight| weight > 0.0)
+            .unwrap_or(0)
+    }
+}
@@ -0,0 +1,774 @@
+//! Sprite types and the frame layout of the bundled `kitty.gif` sheet.
+//!
+//! Frames are `Point`s holding tile offsets on the sheet, which the renderer multiplies by
+//! the tile size and uses as the background position. The top left frame is `Point(0, 0)`
+//! and offsets count leftwards and upwards, so the frame in column `c` and row `r` (both
+//! from 0) is `Point(-c, -r)`. Starting from `ManzarSprites::default()` and replacing some
+//! of its sprites is the easiest way to lay out a custom sheet from Rust.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::{
+    config::{AnimationSpeeds, Fidget, IdleStage, ScratchFrames, SheetLayout},
+    error::ManzarError,
+};
+
+/// `(x, y)`, written as `[x, y]` in JS. For frames both are 0 or negative tile offsets,
+/// see the module docs.
+#[derive(Clone, PartialEq, Deserialize)]
+pub struct Point(pub i32, pub i32);
+
+impl Point {
+    /// Whether the frame lies on a sheet of `columns` x `rows` tiles. Offsets count
+    /// leftwards and upwards, so the frames on the sheet are at 0 and below.
+    fn on_grid(&self, (columns, rows): (u32, u32)) -> bool {
+        let within = |offset: i32, count: u32| offset <= 0 && (-(offset as i64)) < count as i64;
+        within(self.0, columns) && within(self.1, rows)
+    }
+}
+
+/// How long an animation plays, `"infinite"` or `{ "definite": ticks }` in JS
+#[derive(Clone, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnimationDuration {
+    /// Loop until the cat does something else
+    #[default]
+    Infinite,
+    /// Play for this many ticks, then hand over to the next state
+    Definite(u32),
+}
+
+/// Frames played one after another
+#[derive(Clone, PartialEq, Deserialize)]
+pub struct Animation {
+    /// The frames, in order. There has to be at least one.
+    pub states: Vec<Point>,
+    #[serde(default)]
+    pub duration: AnimationDuration,
+    /// Percent of a frame advanced per tick, so 100 shows a new frame every tick and
+    /// 25 every fourth
+    #[serde(default = "default_speed")]
+    pub speed: u32,
+    /// Ticks each frame is shown for, one per state. Replaces `speed` when given.
+    #[serde(default)]
+    pub durations: Option<Vec<u32>>,
+}
+
+fn default_speed() -> u32 {
+    100
+}
+
+impl Animation {
+    /// A looping animation showing a new frame every tick
+    pub fn new(states: Vec<Point>) -> Self {
+        Self {
+            states,
+            duration: AnimationDuration::Infinite,
+            speed: default_speed(),
+            durations: None,
+        }
+    }
+
+    pub(crate) fn is_infinite(&self) -> bool {
+        matches!(self.duration, AnimationDuration::Infinite)
+    }
+
+    /// The frame shown `intervals` ticks into the animation
+    pub(crate) fn frame_at(&self, intervals: u64) -> &Point {
+        let len = self.states.len() as u64;
+        let index = match &self.durations {
+            Some(durations) => {
+                // `check` made sure there is a non-zero duration per frame
+                let cycle: u64 = durations.iter().map(|&ticks| ticks as u64).sum();
+                let mut into_cycle = intervals % cycle.max(1);
+                durations
+                    .iter()
+                    .position(|&ticks| {
+                        let shown = into_cycle < ticks as u64;
+                        into_cycle = into_cycle.saturating_sub(ticks as u64);
+                        shown
+                    })
+                    .unwrap_or(0) as u64
+            }
+            // speed is in percent of one frame per tick. Multiplying first avoids
+            // dividing by zero for a speed of 0.
+            None => intervals * self.speed as u64 / 100 % len,
+        };
+        &self.states[index as usize]
+    }
+}
+
+/// A single frame or an animation, `{ "static": [x, y] }` or
+/// `{ "animated": { "states": [...], ... } }` in JS
+#[derive(Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sprite {
+    Static(Point),
+    Animated(Animation),
+}
+
+/// Sprites for the cat facing north, east, south and west
+#[derive(Clone, Deserialize)]
+pub struct CardinalSprites {
+    pub n: Sprite,
+    pub e: Sprite,
+    pub s: Sprite,
+    pub w: Sprite,
+}
+
+/// Sprites for the cat facing the diagonals
+#[derive(Clone, Deserialize)]
+pub struct OrdinalSprites {
+    pub ne: Sprite,
+    pub se: Sprite,
+    pub sw: Sprite,
+    pub nw: Sprite,
+}
+
+/// Scratching animations. `cat` is played in the open, `cardinal` against the page edge
+/// on that side of the cat.
+#[derive(Clone, Deserialize)]
+pub struct ScratchSprites {
+    pub cat: Sprite,
+    pub cardinal: CardinalSprites,
+}
+
+/// Every sprite a cat uses. The default is the layout of `kitty.gif`, so a custom sheet
+/// is set up by replacing its fields.
+#[derive(Clone)]
+pub struct ManzarSprites {
+    pub idle: Sprite,
+    pub alert: Sprite,
+    pub tired: Sprite,
+    pub sleeping: Sprite,
+    /// After being clicked too often
+    pub angry: Sprite,
+    /// One-shot transition into sleeping
+    pub yawn: Sprite,
+    /// Dropping to the ground in gravity mode
+    pub falling: Sprite,
+    /// Backed into a corner while fleeing
+    pub cower: Sprite,
+    /// Investigating a click with `curious`
+    pub sniff: Sprite,
+    /// Short one-shot played at random while idle
+    pub fidget: Sprite,
+    /// One-shots played when the cat appears and disappears
+    pub poof_in: Sprite,
+    pub poof_out: Sprite,
+    pub cardinal: CardinalSprites,
+    pub ordinal: OrdinalSprites,
+    pub scratch: ScratchSprites,
+    // sprites that idle stages and fidgets brought along, by name
+    pub(crate) stages: HashMap<String, Sprite>,
+}
+
+/// A complete sprite layout for reskinning a running cat, as passed to `set_all_sprites`.
+/// The states the cat doesn't always use may be left out and borrow a similar frame.
+#[derive(Deserialize)]
+pub(crate) struct SpriteLayout {
+    // sheet the frames are on, if it isn't the current one
+    pub(crate) sheet_url: Option<String>,
+    idle: Sprite,
+    alert: Sprite,
+    tired: Sprite,
+    sleeping: Sprite,
+    angry: Option<Sprite>,
+    yawn: Option<Sprite>,
+    falling: Option<Sprite>,
+    cower: Option<Sprite>,
+    sniff: Option<Sprite>,
+    fidget: Option<Sprite>,
+    poof_in: Option<Sprite>,
+    poof_out: Option<Sprite>,
+    cardinal: CardinalSprites,
+    ordinal: OrdinalSprites,
+    scratch: ScratchSprites,
+}
+
+impl From<SpriteLayout> for ManzarSprites {
+    fn from(layout: SpriteLayout) -> Self {
+        Self {
+            angry: layout.angry.unwrap_or_else(|| layout.alert.clone()),
+            yawn: layout.yawn.unwrap_or_else(|| layout.tired.clone()),
+            falling: layout.falling.unwrap_or_else(|| layout.alert.clone()),
+            cower: layout.cower.unwrap_or_else(|| layout.tired.clone()),
+            sniff: layout.sniff.unwrap_or_else(|| layout.tired.clone()),
+            fidget: layout.fidget.unwrap_or_else(|| layout.idle.clone()),
+            // static sprites end right away, so these are simply skipped
+            poof_in: layout.poof_in.unwrap_or_else(|| layout.idle.clone()),
+            poof_out: layout.poof_out.unwrap_or_else(|| layout.idle.clone()),
+            idle: layout.idle,
+            alert: layout.alert,
+            tired: layout.tired,
+            sleeping: layout.sleeping,
+            cardinal: layout.cardinal,
+            ordinal: layout.ordinal,
+            scratch: layout.scratch,
+            stages: HashMap::new(),
+        }
+    }
+}
+
+impl Sprite {
+    /// How many ticks a definite animation plays for
+    pub(crate) fn ticks(&self) -> Option<u32> {
+        match self {
+            Sprite::Animated(Animation {
+                duration: AnimationDuration::Definite(ticks),
+                ..
+            }) => Some(*ticks),
+            _ => None,
+        }
+    }
+
+    /// The sprite held on its first frame
+    pub(crate) fn still(&self) -> Sprite {
+        match self {
+            Sprite::Static(_) => self.clone(),
+            Sprite::Animated(anim) => Sprite::Static(anim.states[0].clone()),
+        }
+    }
+
+    /// Make sure an animation has frames, and one non-zero duration per frame if it has any
+    pub(crate) fn check(&self, name: &str) -> Result<(), ManzarError> {
+        let Sprite::Animated(anim) = self else {
+            return Ok(());
+        };
+        if anim.states.is_empty() {
+            return Err(ManzarError::InvalidConfig(format!(
+                "the {} animation needs at least one frame",
+                name
+            )));
+        }
+        if let Some(durations) = &anim.durations {
+            if durations.len() != anim.states.len() || durations.contains(&0) {
+                return Err(ManzarError::InvalidConfig(format!(
+                    "the {} animation needs a non-zero duration for each of its {} frames, got {:?}",
+                    name,
+                    anim.states.len(),
+                    durations
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The first frame that doesn't lie on a sheet of `grid` (columns, rows) tiles
+    pub(crate) fn off_grid(&self, grid: (u32, u32)) -> Option<&Point> {
+        let frames = match self {
+            Sprite::Static(frame) => std::slice::from_ref(frame),
+            Sprite::Animated(anim) => &anim.states,
+        };
+        frames.iter().find(|frame| !frame.on_grid(grid))
+    }
+
+    fn set_speed(&mut self, speed: u32) {
+        if let Sprite::Animated(anim) = self {
+            anim.speed = speed;
+        }
+    }
+}
+
+impl ManzarSprites {
+    /// Apply the configured scratch frames and duration, making sure every
+    /// scratch animation plays a whole number of cycles
+    pub(crate) fn with_scratch(
+        mut self,
+        frames: &ScratchFrames,
+        duration: Option<u32>,
+    ) -> Result<Self, ManzarError> {
+        let scratch = &mut self.scratch;
+        let groups = [
+            ("cat", &mut scratch.cat, &frames.cat),
+            ("n", &mut scratch.cardinal.n, &frames.n),
+            ("e", &mut scratch.cardinal.e, &frames.e),
+            ("s", &mut scratch.cardinal.s, &frames.s),
+            ("w", &mut scratch.cardinal.w, &frames.w),
+        ];
+        for (name, sprite, frames) in groups {
+            let Sprite::Animated(anim) = sprite else {
+                continue;
+            };
+            if let Some(frames) = frames {
+                if frames.is_empty() {
+                    return Err(ManzarError::InvalidConfig(format!(
+                        "scratch_frames.{} needs at least one frame",
+                        name
+                    )));
+                }
+                anim.states = frames.iter().map(|&(x, y)| Point(x, y)).collect();
+            }
+            if let Some(duration) = duration {
+                let len = anim.states.len() as u32;
+                if duration == 0 || duration % len != 0 {
+                    return Err(ManzarError::InvalidConfig(format!(
+                        "scratch_duration ({}) must be a non-zero multiple of the {} frames of scratch_frames.{}",
+                        duration, len, name
+                    )));
+                }
+                anim.duration = AnimationDuration::Definite(duration);
+            }
+        }
+        Ok(self)
+    }
+
+    /// Move the sprites `layout` lists to their frames on the sheet. Animations keep
+    /// their duration and speed, and only the frames they go through change.
+    pub(crate) fn with_layout(mut self, layout: &SheetLayout) -> Result<Self, ManzarError> {
+        // a named layout is the default one, as long as kitty.gif is the only one
+        let SheetLayout::Frames(layout) = layout else {
+            return Ok(self);
+        };
+        // sorted so a broken layout always reports the same error first
+        let mut names: Vec<&String> = layout.keys().collect();
+        names.sort();
+        for name in names {
+            let frames = &layout[name];
+            if frames.is_empty() {
+                return Err(ManzarError::InvalidConfig(format!(
+                    "sheet_layout.{} needs at least one frame",
+                    name
+                )));
+            }
+            if let Some((x, y)) = frames.iter().find(|&&(x, y)| x > 0 || y > 0) {
+                return Err(ManzarError::InvalidConfig(format!(
+                    "sheet_layout.{} has a frame at [{}, {}], but frames are tile offsets of 0 or less",
+                    name, x, y
+                )));
+            }
+            let Some(sprite) = self.get_mut(name) else {
+                return Err(ManzarError::InvalidConfig(format!(
+                    "sheet_layout has frames for {}, which isn't a sprite name",
+                    name
+                )));
+            };
+            let states: Vec<Point> = frames.iter().map(|&(x, y)| Point(x, y)).collect();
+            *sprite = match sprite {
+                Sprite::Animated(anim) => Sprite::Animated(Animation {
+                    states,
+                    // they were timed for the old frames
+                    durations: None,
+                    ..anim.clone()
+                }),
+                Sprite::Static(_) if states.len() == 1 => Sprite::Static(states[0].clone()),
+                Sprite::Static(_) => Sprite::Animated(Animation::new(states)),
+            };
+        }
+        Ok(self)
+    }
+
+    /// The walking sprite for a compass direction like `"NW"`. The second value tells
+    /// whether it has to be mirrored, which happens for westward directions when `mirror` is on.
+    pub(crate) fn walking(&self, direction: &str, mirror: bool) -> (&Sprite, bool) {
+        let c = &self.cardinal;
+        let o = &self.ordinal;
+        match direction {
+            "N" => (&c.n, false),
+            "E" => (&c.e, false),
+            "W" if mirror => (&c.e, true),
+            "W" => (&c.w, false),
+            "S" => (&c.s, false),
+            "NE" => (&o.ne, false),
+            "NW" if mirror => (&o.ne, true),
+            "NW" => (&o.nw, false),
+            "SE" => (&o.se, false),
+            "SW" if mirror => (&o.se, true),
+            "SW" => (&o.sw, false),
+            _ => panic!("Invalid direction!"),
+        }
+    }
+
+    /// The scratch sprite for the closest wall less than `margin` away, given the
+    /// gaps to the west, north, east and south walls. Without one the cat scratches itself.
+    pub(crate) fn scratching(&self, gaps: [i32; 4], margin: i32) -> &Sprite {
+        let walls = [
+            &self.scratch.cardinal.w,
+            &self.scratch.cardinal.n,
+            &self.scratch.cardinal.e,
+            &self.scratch.cardinal.s,
+        ];
+        gaps.iter()
+            .zip(walls)
+            .filter(|(gap, _)| **gap < margin)
+            .min_by_key(|(gap, _)| **gap)
+            .map_or(&self.scratch.cat, |(_, sprite)| sprite)
+    }
+
+    /// Add the sprites idle stages bring along, and make sure the others name an
+    /// existing sprite
+    pub(crate) fn with_stages(mut self, stages: &[IdleStage]) -> Result<Self, ManzarError> {
+        for stage in stages {
+            let Some(frames) = &stage.frames else {
+                if self.get_mut(&stage.sprite).is_none() {
+                    return Err(ManzarError::InvalidConfig(format!(
+                        "idle stage {:?} needs frames, it isn't one of the state sprites",
+                        stage.sprite
+                    )));
+                }
+                continue;
+            };
+            let states: Vec<Point> = frames.iter().map(|&(x, y)| Point(x, y)).collect();
+            let sprite = match states.as_slice() {
+                [] => {
+                    return Err(ManzarError::InvalidConfig(format!(
+                        "idle stage {:?} needs at least one frame",
+                        stage.sprite
+                    )))
+                }
+                [frame] => Sprite::Static(frame.clone()),
+                _ => Sprite::Animated(Animation {
+                    states,
+                    duration: AnimationDuration::Infinite,
+                    speed: 100,
+                    durations: None,
+                }),
+            };
+            match self.get_mut(&stage.sprite) {
+                Some(slot) => *slot = sprite,
+                None => {
+                    self.stages.insert(stage.sprite.clone(), sprite);
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// Add the sprites fidgets bring along, each played through once, and make
+    /// sure the others name an existing sprite
+    pub(crate) fn with_fidgets(mut self, fidgets: &[Fidget]) -> Result<Self, ManzarError> {
+        for fidget in fidgets {
+            let Some(frames) = &fidget.frames else {
+                match self.get_mut(&fidget.sprite) {
+                    None => {
+                        return Err(ManzarError::InvalidConfig(format!(
+                            "fidget {:?} needs frames, it isn't one of the state sprites",
+                            fidget.sprite
+                        )))
+                    }
+                    // a looping one would never hand back to the idle sprite
+                    Some(sprite) if sprite.ticks().is_none() => {
+                        return Err(ManzarError::InvalidConfig(format!(
+                            "fidget {:?} has to be an animation with a definite duration",
+                            fidget.sprite
+                        )))
+                    }
+                    Some(_) => continue,
+                }
+            };
+            if frames.is_empty() {
+                return Err(ManzarError::InvalidConfig(format!(
+                    "fidget {:?} needs at least one frame",
+                    fidget.sprite
+                )));
+            }
+            let sprite = Sprite::Animated(Animation {
+                states: frames.iter().map(|&(x, y)| Point(x, y)).collect(),
+                duration: AnimationDuration::Definite(frames.len() as u32),
+                speed: 100,
+                durations: None,
+            });
+            match self.get_mut(&fidget.sprite) {
+                Some(slot) => *slot = sprite,
+                None => {
+                    self.stages.insert(fidget.sprite.clone(), sprite);
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// Every sprite with the name `get_mut` knows it by
+    fn named(&self) -> Vec<(&str, &Sprite)> {
+        let mut named = vec![
+            ("idle", &self.idle),
+            ("alert", &self.alert),
+            ("tired", &self.tired),
+            ("sleeping", &self.sleeping),
+            ("angry", &self.angry),
+            ("yawn", &self.yawn),
+            ("falling", &self.falling),
+            ("cower", &self.cower),
+            ("sniff", &self.sniff),
+            ("fidget", &self.fidget),
+            ("poof_in", &self.poof_in),
+            ("poof_out", &self.poof_out),
+            ("n", &self.cardinal.n),
+            ("e", &self.cardinal.e),
+            ("s", &self.cardinal.s),
+            ("w", &self.cardinal.w),
+            ("ne", &self.ordinal.ne),
+            ("se", &self.ordinal.se),
+            ("sw", &self.ordinal.sw),
+            ("nw", &self.ordinal.nw),
+            ("scratch", &self.scratch.cat),
+            ("scratch_n", &self.scratch.cardinal.n),
+            ("scratch_e", &self.scratch.cardinal.e),
+            ("scratch_s", &self.scratch.cardinal.s),
+            ("scratch_w", &self.scratch.cardinal.w),
+        ];
+        named.extend(
+            self.stages
+                .iter()
+                .map(|(name, sprite)| (name.as_str(), sprite)),
+        );
+        named
+    }
+
+    /// Run `Sprite::check` on every sprite
+    pub(crate) fn check(&self) -> Result<(), ManzarError> {
+        for (name, sprite) in self.named() {
+            sprite.check(name)?;
+        }
+        Ok(())
+    }
+
+    /// Make sure every frame lies on a sheet of `grid` (columns, rows) tiles,
+    /// listing the sprites that reach past it otherwise
+    pub(crate) fn check_grid(&self, grid: (u32, u32)) -> Result<(), ManzarError> {
+        let outside: Vec<String> = self
+            .named()
+            .into_iter()
+            .filter_map(|(name, sprite)| {
+                let frame = sprite.off_grid(grid)?;
+                Some(format!("{} at [{}, {}]", name, frame.0, frame.1))
+            })
+            .collect();
+        if outside.is_empty() {
+            return Ok(());
+        }
+        Err(ManzarError::InvalidConfig(format!(
+            "frames outside the {}x{} sheet: {}",
+            grid.0,
+            grid.1,
+            outside.join(", ")
+        )))
+    }
+
+    /// Apply the configured speeds to the resting states
+    pub(crate) fn with_speeds(mut self, speeds: &AnimationSpeeds) -> Self {
+        self.idle.set_speed(speeds.idle);
+        self.alert.set_speed(speeds.alert);
+        self.tired.set_speed(speeds.tired);
+        self.sleeping.set_speed(speeds.sleeping);
+        self
+    }
+
+    /// Look up the sprite used for a named state, e.g. `"sleeping"`, `"ne"` or `"scratch_w"`
+    pub(crate) fn get_mut(&mut self, state: &str) -> Option<&mut Sprite> {
+        let sprite = match state {
+            "idle" => &mut self.idle,
+            "alert" => &mut self.alert,
+            "tired" => &mut self.tired,
+            "sleeping" => &mut self.sleeping,
+            "angry" => &mut self.angry,
+            "yawn" => &mut self.yawn,
+            "falling" => &mut self.falling,
+            "cower" => &mut self.cower,
+            "sniff" => &mut self.sniff,
+            "fidget" => &mut self.fidget,
+            "poof_in" => &mut self.poof_in,
+            "poof_out" => &mut self.poof_out,
+            "n" => &mut self.cardinal.n,
+            "e" => &mut self.cardinal.e,
+            "s" => &mut self.cardinal.s,
+            "w" => &mut self.cardinal.w,
+            "ne" => &mut self.ordinal.ne,
+            "se" => &mut self.ordinal.se,
+            "sw" => &mut self.ordinal.sw,
+            "nw" => &mut self.ordinal.nw,
+            "scratch" => &mut self.scratch.cat,
+            "scratch_n" => &mut self.scratch.cardinal.n,
+            "scratch_e" => &mut self.scratch.cardinal.e,
+            "scratch_s" => &mut self.scratch.cardinal.s,
+            "scratch_w" => &mut self.scratch.cardinal.w,
+            _ => return self.stages.get_mut(state),
+        };
+        Some(sprite)
+    }
+}
+
+/// The layout of the bundled `kitty.gif` sheet
+impl Default for ManzarSprites {
+    fn default() -> Self {
+        let cardinal = CardinalSprites {
+            n: Sprite::Animated(Animation {
+                states: vec![Point(-1, -2), Point(-1, -3)],
+                duration: AnimationDuration::Infinite,
+                speed: 100,
+                durations: None,
+            }),
+            e: Sprite::Animated(Animation {
+                states: vec![Point(-3, 0), Point(-3, -1)],
+                duration: AnimationDuration::Infinite,
+                speed: 100,
+                durations: None,
+            }),
+            s: Sprite::Animated(Animation {
+                states: vec![Point(-6, -3), Point(-7, -2)],
+                duration: AnimationDuration::Infinite,
+                speed: 100,
+                durations: None,
+            }),
+            w: Sprite::Animated(Animation {
+                states: vec![Point(-4, -2), Point(-4, -3)],
+                duration: AnimationDuration::Infinite,
+                speed: 100,
+                durations: None,
+            }),
+        };
+
+        let ordinal = OrdinalSprites {
+            ne: Sprite::Animated(Animation {
+                states: vec![Point(0, -2), Point(0, -3)],
+                duration: AnimationDuration::Infinite,
+                speed: 100,
+                durations: None,
+            }),
+            se: Sprite::Animated(Animation {
+                states: vec![Point(-5, -1), Point(-5, -2)],
+                duration: AnimationDuration::Infinite,
+                speed: 100,
+                durations: None,
+            }),
+            sw: Sprite::Animated(Animation {
+                states: vec![Point(-5, -3), Point(-6, -1)],
+                duration: AnimationDuration::Infinite,
+                speed: 100,
+                durations: None,
+            }),
+            nw: Sprite::Animated(Animation {
+                states: vec![Point(-1, 0), Point(-1, -1)],
+                duration: AnimationDuration::Infinite,
+                speed: 100,
+                durations: None,
+            }),
+        };
+
+        let scratch = ScratchSprites {
+            cat: Sprite::Animated(Animation {
+                states: vec![Point(-5, 0), Point(-6, 0), Point(-7, 0)],
+                duration: AnimationDuration::Definite(20),
+                speed: 100,
+                durations: None,
+            }),
+            cardinal: CardinalSprites {
+                n: Sprite::Animated(Animation {
+                    states: vec![Point(0, 0), Point(0, -1)],
+                    duration: AnimationDuration::Definite(20),
+                    speed: 100,
+                    durations: None,
+                }),
+                e: Sprite::Animated(Animation {
+                    states: vec![Point(-2, -2), Point(-2, -3)],
+                    duration: AnimationDuration::Definite(20),
+                    speed: 100,
+                    durations: None,
+                }),
+                w: Sprite::Animated(Animation {
+                    states: vec![Point(-4, 0), Point(-4, -1)],
+                    duration: AnimationDuration::Definite(20),
+                    speed: 100,
+                    durations: None,
+                }),
+                s: Sprite::Animated(Animation {
+                    states: vec![Point(-7, -1), Point(-6, -2)],
+                    duration: AnimationDuration::Definite(20),
+                    speed: 100,
+                    durations: None,
+                }),
+            },
+        };
+
+        ManzarSprites {
+            idle: Sprite::Static(Point(-3, -3)),
+            alert: Sprite::Static(Point(-7, -3)),
+            tired: Sprite::Static(Point(-3, -2)),
+            sleeping: Sprite::Animated(Animation {
+                states: vec![Point(-2, 0), Point(-2, -1)],
+                duration: AnimationDuration::Infinite,
+                speed: 25,
+                durations: None,
+            }),
+            angry: Sprite::Animated(Animation {
+                states: vec![Point(-7, -3), Point(-5, 0)],
+                duration: AnimationDuration::Infinite,
+                speed: 100,
+                durations: None,
+            }),
+            yawn: Sprite::Animated(Animation {
+                states: vec![Point(-3, -2), Point(-3, -3)],
+                duration: AnimationDuration::Definite(3),
+                speed: 50,
+                durations: None,
+            }),
+            // the sheet has no falling frames, the startled pose reads well enough
+            falling: Sprite::Static(Point(-7, -3)),
+            // nor cowering ones, so it crouches down like when tired
+            cower: Sprite::Static(Point(-3, -2)),
+            // nor sniffing ones, bobbing the head down and up stands in
+            sniff: Sprite::Animated(Animation {
+                states: vec![Point(-3, -2), Point(-3, -3)],
+                duration: AnimationDuration::Infinite,
+                speed: 50,
+                durations: None,
+            }),
+            // a quick prick of the ears
+            fidget: Sprite::Animated(Animation {
+                states: vec![Point(-7, -3), Point(-3, -3)],
+                duration: AnimationDuration::Definite(2),
+                speed: 50,
+                durations: None,
+            }),
+            // nor poof frames. Static sprites end right away, so these are skipped.
+            poof_in: Sprite::Static(Point(-3, -3)),
+            poof_out: Sprite::Static(Point(-3, -3)),
+            cardinal,
+            ordinal,
+            scratch,
+            stages: HashMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(speed: u32) -> Animation {
+        Animation {
+            speed,
+            ..Animation::new(vec![Point(0, 0), Point(-1, 0), Point(-2, 0)])
+        }
+    }
+
+    fn shown(animation: &Animation, ticks: u64) -> Vec<i32> {
+        (0..ticks).map(|tick| animation.frame_at(tick).0).collect()
+    }
+
+    #[test]
+    fn a_speed_of_0_holds_the_first_frame() {
+        assert_eq!(shown(&frames(0), 4), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn a_speed_of_100_shows_a_new_frame_every_tick() {
+        assert_eq!(shown(&frames(100), 5), [0, -1, -2, 0, -1]);
+    }
+
+    #[test]
+    fn a_speed_of_200_skips_every_other_frame() {
+        assert_eq!(shown(&frames(200), 4), [0, -2, -1, 0]);
+    }
+
+    #[test]
+    fn a_frame_with_a_long_duration_is_held() {
+        let blink = Animation {
+            durations: Some(vec![1, 3, 1]),
+            ..frames(100)
+        };
+        assert_eq!(shown(&blink, 7), [0, -1, -1, -1, -2, 0, -1]);
+    }
+}
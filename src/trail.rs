@@ -0,0 +1,98 @@
+//! Fading copies of the cat left behind where it has been. The copies are a
+//! fixed pool of elements created on first use and then recycled, so a moving
+//! cat doesn't add and remove DOM nodes on every tick.
+
+use std::collections::VecDeque;
+
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::HtmlElement;
+
+use crate::error::ManzarError;
+
+// How long a trail copy lingers before it is hidden, in ms
+const TRAIL_LIFETIME: f64 = 400.0;
+
+struct Ghost {
+    element: HtmlElement,
+    // clock time the copy was last placed, in ms
+    placed: f64,
+    shown: bool,
+}
+
+pub(crate) struct Trail {
+    // oldest first, so hidden copies are always at the front
+    ghosts: VecDeque<Ghost>,
+    capacity: usize,
+}
+
+impl Trail {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            ghosts: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Leave a copy of `cat` where it is right now, reusing the oldest copy
+    /// once the pool is full
+    pub(crate) fn spawn(&mut self, cat: &HtmlElement, now: f64) -> Result<(), ManzarError> {
+        let element = if self.ghosts.len() < self.capacity {
+            let element = cat
+                .clone_node()?
+                .dyn_into::<HtmlElement>()
+                .map_err(JsValue::from)?;
+            element.remove_attribute("id")?;
+            // insert before the cat so it is drawn underneath
+            cat.before_with_node_1(&element)?;
+            element
+        } else {
+            match self.ghosts.pop_front() {
+                Some(oldest) => oldest.element,
+                None => return Ok(()),
+            }
+        };
+
+        // the cat's inline style carries its position and frame, and replacing
+        // it wholesale also takes back the `display: none` of a hidden copy
+        element.style().set_css_text(&cat.style().css_text());
+        element.style().set_property("pointer-events", "none")?;
+        self.ghosts.push_back(Ghost {
+            element,
+            placed: now,
+            shown: true,
+        });
+        self.fade()
+    }
+
+    /// Hide the copies that have been around longer than their lifetime
+    pub(crate) fn expire(&mut self, now: f64) -> Result<(), ManzarError> {
+        for ghost in self.ghosts.iter_mut() {
+            if ghost.shown && now - ghost.placed >= TRAIL_LIFETIME {
+                ghost.element.style().set_property("display", "none")?;
+                ghost.shown = false;
+            }
+        }
+        Ok(())
+    }
+
+    /// Older copies are fainter
+    fn fade(&self) -> Result<(), ManzarError> {
+        let shown: Vec<_> = self.ghosts.iter().filter(|ghost| ghost.shown).collect();
+        let len = shown.len() as f32;
+        for (i, ghost) in shown.iter().enumerate() {
+            let opacity = 0.5 * (i + 1) as f32 / (len + 1.0);
+            ghost
+                .element
+                .style()
+                .set_property("opacity", &opacity.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Take every copy off the page
+    pub(crate) fn remove(&mut self) {
+        for ghost in self.ghosts.drain(..) {
+            ghost.element.remove();
+        }
+    }
+}